@@ -0,0 +1,77 @@
+//! Measures the overhead of the redirection primitives.
+
+#[cfg(unix)]
+mod unix
+{
+    use criterion::{BenchmarkId, Criterion, Throughput};
+    use io_redirect::{capture_stderr, tee_redirect, Redirectable, RedirectableExt};
+    use std::fs::{File, OpenOptions};
+    use std::io::Write;
+    use std::os::fd::AsRawFd;
+
+    fn null() -> File {
+        OpenOptions::new().write(true).open("/dev/null").unwrap()
+    }
+
+    /// A single `dup2` between two open files.
+    pub fn redirect_fd(c: &mut Criterion) {
+        let mut src = null();
+        let dst = null();
+        c.bench_function("redirect_fd_to_fd", |b| b.iter(|| src.redirect(&dst).unwrap()));
+    }
+
+    /// Opening a file by path and redirecting to it.
+    pub fn redirect_path(c: &mut Criterion) {
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().join("dst.txt");
+        let mut src = null();
+        c.bench_function("redirect_to_path", |b| b.iter(|| src.redirect(path.as_path()).unwrap()));
+    }
+
+    /// Saving the original destination, redirecting, writing once and restoring on drop.
+    pub fn guard_round_trip(c: &mut Criterion) {
+        let mut src = null();
+        let dst = null();
+        c.bench_function("redirect_guard_round_trip", |b| {
+            b.iter(|| {
+                let guard = src.save_and_redirect(&dst).unwrap();
+                src.write_all(b"x").unwrap();
+                drop(guard);
+            })
+        });
+    }
+
+    /// Writes through a tee into the null device.
+    pub fn tee_throughput(c: &mut Criterion) {
+        let mut group = c.benchmark_group("tee_redirect");
+        for size in [4 * 1024, 64 * 1024] {
+            let mut src = null();
+            let mirror = null();
+            let tee = tee_redirect(src.as_raw_fd(), &mirror).unwrap();
+            let chunk = vec![0u8; size];
+            group.throughput(Throughput::Bytes(size as u64));
+            group.bench_with_input(BenchmarkId::new("write", size), &size, |b, _| b.iter(|| src.write_all(&chunk).unwrap()));
+            drop(tee);
+        }
+        group.finish();
+    }
+
+    /// Starting a capture, writing once and collecting the captured bytes.
+    pub fn capture_round_trip(c: &mut Criterion) {
+        c.bench_function("capture_round_trip", |b| {
+            b.iter(|| {
+                let capture = capture_stderr().unwrap();
+                eprint!("x");
+                assert_eq!(capture.finish().unwrap(), b"x");
+            })
+        });
+    }
+}
+
+#[cfg(unix)]
+criterion::criterion_group!(benches, unix::redirect_fd, unix::redirect_path, unix::guard_round_trip, unix::tee_throughput, unix::capture_round_trip);
+#[cfg(unix)]
+criterion::criterion_main!(benches);
+
+#[cfg(not(unix))]
+fn main() {}
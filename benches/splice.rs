@@ -0,0 +1,60 @@
+//! Compares `splice_loop` with `io::copy` for moving data between two pipes.
+
+#[cfg(target_os = "linux")]
+mod linux
+{
+    use criterion::{BenchmarkId, Criterion, Throughput};
+    use io_redirect::unix::splice_loop;
+    use std::fs::File;
+    use std::io::{self, Read, Write};
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd};
+
+    fn pipe() -> (File, File) {
+        let mut fds = [0; 2];
+        assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+        unsafe { (File::from(OwnedFd::from_raw_fd(fds[0])), File::from(OwnedFd::from_raw_fd(fds[1]))) }
+    }
+
+    /// Hides the descriptor of a file so that `io::copy` falls back to copying through userspace.
+    struct Userspace<'a>(&'a mut File);
+
+    impl Read for Userspace<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.0.read(buf)
+        }
+    }
+
+    /// Feeds `size` bytes through a pipe, moves them to a second pipe with `transfer` and drains it.
+    fn run(size: usize, transfer: impl FnOnce(&mut File, &mut File) -> io::Result<u64>) {
+        let (mut src_read, mut src_write) = pipe();
+        let (mut dst_read, mut dst_write) = pipe();
+        let writer = std::thread::spawn(move || src_write.write_all(&vec![0u8; size]));
+        let reader = std::thread::spawn(move || io::copy(&mut dst_read, &mut io::sink()));
+        transfer(&mut src_read, &mut dst_write).unwrap();
+        drop(dst_write);
+        writer.join().unwrap().unwrap();
+        reader.join().unwrap().unwrap();
+    }
+
+    pub fn bench(c: &mut Criterion) {
+        let mut group = c.benchmark_group("pipe_to_pipe");
+        for size in [64 * 1024, 4 * 1024 * 1024] {
+            group.throughput(Throughput::Bytes(size as u64));
+            group.bench_with_input(BenchmarkId::new("splice_loop", size), &size, |b, &size| {
+                b.iter(|| run(size, |src, dst| splice_loop(src.as_raw_fd(), dst.as_raw_fd())));
+            });
+            group.bench_with_input(BenchmarkId::new("io_copy", size), &size, |b, &size| {
+                b.iter(|| run(size, |src, dst| io::copy(&mut Userspace(src), dst)));
+            });
+        }
+        group.finish();
+    }
+}
+
+#[cfg(target_os = "linux")]
+criterion::criterion_group!(benches, linux::bench);
+#[cfg(target_os = "linux")]
+criterion::criterion_main!(benches);
+
+#[cfg(not(target_os = "linux"))]
+fn main() {}
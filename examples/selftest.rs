@@ -1,17 +1,73 @@
 use std::fs::File;
-use std::io::{stderr, stdout, Read, Write};
-use io_redirect::redirect_std_to_path;
+use std::io::{stderr, stdin, stdout, Read, Write};
+use io_redirect::{redirect_all_std_to_path, redirect_std_pair, redirect_std_to_file, redirect_std_to_null, redirect_std_to_path, redirect_std_to_path_exclusive, redirect_std_to_path_with_options, redirect_stderr_to_stdout, redirect_stdin_from_path, redirect_stdout_to_stderr, reset_std_to_tty, RedirectOptions, Redirectable};
+#[cfg(unix)]
+use io_redirect::{capture_stderr, capture_stdout, redirect_stdin_from_bytes, tee_redirect};
+#[cfg(unix)]
+use std::os::fd::AsRawFd;
 
-/// This executable demonstrates the process of redirecting both `stdout`
-/// and `stderr` to a specified file path and validating that the contents
-/// of the file match the expected output.
+/// This executable demonstrates redirection of the standard streams. Each scenario redirects
+/// the streams of this process and validates that the redirection took effect. The scenario is
+/// selected by the first argument and defaults to `std_to_path`.
 fn main() {
+    match std::env::args().nth(1).as_deref() {
+        None | Some("std_to_path") => std_to_path(),
+        Some("stdin_from_path") => stdin_from_path(),
+        Some("all_std_to_path") => all_std_to_path(),
+        #[cfg(unix)] Some("tee_stderr") => tee_stderr(),
+        Some("std_to_null") => std_to_null(),
+        #[cfg(unix)] Some("capture_std") => capture_std(),
+        Some("stdin_to_file") => stdin_to_file(),
+        Some("stdout_to_file") => stdout_to_file(),
+        Some("std_to_file") => std_to_file(),
+        Some("stdio_to_devnull") => stdio_to_devnull(),
+        Some("reset_to_tty") => reset_to_tty(),
+        Some("stdout_to_stderr") => stdout_to_stderr(),
+        Some("stderr_to_stdout") => stderr_to_stdout(),
+        #[cfg(unix)] Some("stdin_from_bytes") => stdin_from_bytes(),
+        Some("std_to_path_with_options") => std_to_path_with_options(),
+        Some("stdin_from_path_with_options") => stdin_from_path_with_options(),
+        #[cfg(all(target_os = "linux", feature = "linux-memfd"))] Some("stdout_to_memfd") => stdout_to_memfd(),
+        Some("std_pair") => std_pair(),
+        #[cfg(all(windows, feature = "windows-sys"))] Some("stdout_to_named_pipe") => stdout_to_named_pipe(),
+        #[cfg(unix)] Some("intercept_stdout") => intercept_stdout(),
+        #[cfg(unix)] Some("save_restore_std") => save_restore_std(),
+        Some("std_to_path_exclusive") => std_to_path_exclusive(),
+        #[cfg(unix)] Some("builder") => builder(),
+        Some("stdin_from_reader") => stdin_from_reader(),
+        #[cfg(unix)] Some("stdout_to_syslog") => stdout_to_syslog(),
+        #[cfg(all(target_os = "linux", feature = "systemd"))] Some("std_to_journald") => std_to_journald(),
+        #[cfg(unix)] Some("close_inherited_fds") => close_inherited_fds(),
+        #[cfg(unix)] Some("stdout_to_unix_socket") => stdout_to_unix_socket(),
+        #[cfg(unix)] Some("stdout_to_tcp_stream") => stdout_to_tcp_stream(),
+        Some("stdin_redirected_twice") => stdin_redirected_twice(),
+        #[cfg(unix)] Some("std_to_path_guarded") => std_to_path_guarded(),
+        Some("redirect_active") => redirect_active(),
+        #[cfg(unix)] Some("capture_fd_output") => capture_fd_output(),
+        #[cfg(unix)] Some("std_to_path_with_umask") => std_to_path_with_umask(),
+        #[cfg(unix)] Some("redirect_and_exec") => redirect_and_exec(),
+        Some("std_from_env") => std_from_env(),
+        #[cfg(unix)] Some("redirect_while_signalled") => redirect_while_signalled(),
+        #[cfg(unix)] Some("swap_std_pair") => swap_std_pair(),
+        #[cfg(unix)] Some("stderr_tee_to_path") => stderr_tee_to_path(),
+        Some("try_std_to_path_with_options") => try_std_to_path_with_options(),
+        Some("file_to_stdout") => file_to_stdout(),
+        Some("stderr_to_file") => stderr_to_file(),
+        #[cfg(unix)] Some("fd_range") => fd_range(),
+        Some("stdout_to_os_string_from_env") => stdout_to_os_string_from_env(),
+        Some(other) => panic!("unknown scenario: {other}"),
+    }
+}
+
+/// Redirects both `stdout` and `stderr` to a specified file path and validates
+/// that the contents of the file match the expected output.
+fn std_to_path() {
     // Arrange
     let tempdir = tempfile::tempdir().unwrap();
     let log_path = tempdir.path().join("log.txt");
 
     // Act
-    redirect_std_to_path(log_path.as_path(), true).unwrap();
+    redirect_std_to_path(&log_path, true).unwrap();
     print!("Hello to stdout!");
     stdout().flush().unwrap();
     eprint!("Hello to stderr!");
@@ -21,4 +77,945 @@ fn main() {
     let mut dst_contents = String::new();
     File::open(&log_path).unwrap().read_to_string(&mut dst_contents).unwrap();
     assert_eq!(dst_contents, "Hello to stdout!Hello to stderr!");
+}
+
+/// Redirects `stdin` to read from a file with known content and validates
+/// that reading `stdin` yields that content.
+fn stdin_from_path() {
+    // Arrange
+    let tempdir = tempfile::tempdir().unwrap();
+    let input_path = tempdir.path().join("input.txt");
+    std::fs::write(&input_path, "Hello from a file!\n").unwrap();
+
+    // Act
+    redirect_stdin_from_path(&input_path).unwrap();
+    let mut src_contents = String::new();
+    stdin().read_to_string(&mut src_contents).unwrap();
+
+    // Assert
+    assert_eq!(src_contents, "Hello from a file!\n");
+}
+
+/// Redirects `stdout` and `stderr` to a file and `stdin` to the null device, then
+/// validates the file contents and that `stdin` is at end of file.
+fn all_std_to_path() {
+    // Arrange
+    let tempdir = tempfile::tempdir().unwrap();
+    let log_path = tempdir.path().join("log.txt");
+
+    // Act
+    redirect_all_std_to_path(&log_path, false).unwrap();
+    print!("Hello to stdout!");
+    stdout().flush().unwrap();
+    eprint!("Hello to stderr!");
+    stderr().flush().unwrap();
+    let mut src_contents = String::new();
+    stdin().read_to_string(&mut src_contents).unwrap();
+
+    // Assert
+    assert_eq!(std::fs::read_to_string(&log_path).unwrap(), "Hello to stdout!Hello to stderr!");
+    assert_eq!(src_contents, "");
+}
+
+/// Redirects `stderr` to a file, then mirrors it to a second file with a tee and
+/// validates that the lines written to `stderr` appear in both files.
+#[cfg(unix)]
+fn tee_stderr() {
+    // Arrange
+    let tempdir = tempfile::tempdir().unwrap();
+    let original_path = tempdir.path().join("original.txt");
+    let mirror_path = tempdir.path().join("mirror.txt");
+    stderr().redirect(original_path.as_path()).unwrap();
+    let mirror = File::create(&mirror_path).unwrap();
+
+    // Act
+    let tee = tee_redirect(stderr().as_raw_fd(), &mirror).unwrap();
+    eprintln!("first line");
+    eprintln!("second line");
+    eprintln!("third line");
+    drop(tee);
+
+    // Assert
+    let expected = "first line\nsecond line\nthird line\n";
+    assert_eq!(std::fs::read_to_string(&original_path).unwrap(), expected);
+    assert_eq!(std::fs::read_to_string(&mirror_path).unwrap(), expected);
+}
+
+/// Redirects all standard streams to the null device and validates that writing
+/// to `stdout` and `stderr` succeeds and that `stdin` is at end of file.
+fn std_to_null() {
+    // Act
+    redirect_std_to_null(true).unwrap();
+    print!("Hello to nowhere!");
+    stdout().flush().unwrap();
+    eprint!("Hello to nowhere!");
+    stderr().flush().unwrap();
+    let mut src_contents = String::new();
+    stdin().read_to_string(&mut src_contents).unwrap();
+
+    // Assert
+    assert_eq!(src_contents, "");
+}
+
+/// Captures `stdout` and `stderr` in turn and validates the captured bytes.
+#[cfg(unix)]
+fn capture_std() {
+    // Act
+    let capture = capture_stdout().unwrap();
+    print!("hello");
+    let captured_stdout = capture.finish().unwrap();
+    let capture = capture_stderr().unwrap();
+    eprint!("world");
+    let captured_stderr = capture.finish().unwrap();
+
+    // Assert
+    assert_eq!(captured_stdout, b"hello");
+    assert_eq!(captured_stderr, b"world");
+}
+
+/// Redirects `stdin` to an open file with known content and validates that
+/// reading `stdin` yields that content.
+fn stdin_to_file() {
+    // Arrange
+    let tempdir = tempfile::tempdir().unwrap();
+    let input_path = tempdir.path().join("input.txt");
+    std::fs::write(&input_path, "Hello from an open file!\n").unwrap();
+    let input = File::open(&input_path).unwrap();
+
+    // Act
+    stdin().redirect(&input).unwrap();
+    let mut src_contents = String::new();
+    stdin().read_to_string(&mut src_contents).unwrap();
+
+    // Assert
+    assert_eq!(src_contents, "Hello from an open file!\n");
+}
+
+/// Redirects `stdout` to an already opened file and validates that `stderr` is left untouched
+/// while `stdout` output reaches the file.
+fn stdout_to_file() {
+    // Arrange
+    let tempdir = tempfile::tempdir().unwrap();
+    let log_path = tempdir.path().join("log.txt");
+    let log_file = File::create(&log_path).unwrap();
+
+    // Act
+    redirect_std_to_file(&log_file, false).unwrap();
+    print!("Hello to stdout!");
+    stdout().flush().unwrap();
+    eprint!("Hello to stderr!");
+    stderr().flush().unwrap();
+
+    // Assert
+    assert_eq!(std::fs::read_to_string(&log_path).unwrap(), "Hello to stdout!");
+}
+
+/// Redirects both `stdout` and `stderr` to an already opened file and validates
+/// that the contents of the file match the expected output.
+fn std_to_file() {
+    // Arrange
+    let tempdir = tempfile::tempdir().unwrap();
+    let log_path = tempdir.path().join("log.txt");
+    let log_file = File::create(&log_path).unwrap();
+
+    // Act
+    redirect_std_to_file(&log_file, true).unwrap();
+    print!("Hello to stdout!");
+    stdout().flush().unwrap();
+    eprint!("Hello to stderr!");
+    stderr().flush().unwrap();
+
+    // Assert
+    assert_eq!(std::fs::read_to_string(&log_path).unwrap(), "Hello to stdout!Hello to stderr!");
+}
+
+/// Redirects all three standard streams to the null device, then writes to `stdout` and
+/// `stderr` and validates that `stdin` is at end of file. The caller checks that nothing
+/// was written to the original streams.
+fn stdio_to_devnull() {
+    // Act
+    io_redirect::stdio_to_devnull().unwrap();
+    print!("Hello to stdout!");
+    stdout().flush().unwrap();
+    eprint!("Hello to stderr!");
+    stderr().flush().unwrap();
+    let mut src_contents = String::new();
+    stdin().read_to_string(&mut src_contents).unwrap();
+
+    // Assert
+    assert_eq!(src_contents, "");
+}
+
+/// Redirects `stdout` to a file and then back to the terminal. Without a controlling terminal,
+/// validates that the reset reports `NotConnected` and leaves `stdout` redirected.
+fn reset_to_tty() {
+    // Arrange
+    let tempdir = tempfile::tempdir().unwrap();
+    let log_path = tempdir.path().join("log.txt");
+    let tty_reachable = File::options().read(true).write(true).open(if cfg!(windows) { "CONOUT$" } else { "/dev/tty" }).is_ok();
+    let log_file = File::create(&log_path).unwrap();
+    stdout().redirect(&log_file).unwrap();
+
+    // Act
+    let result = reset_std_to_tty(false);
+    print!("Hello to stdout!");
+    stdout().flush().unwrap();
+
+    // Assert
+    if tty_reachable {
+        result.unwrap();
+        assert_eq!(std::fs::read_to_string(&log_path).unwrap(), "");
+    } else {
+        assert_eq!(result.unwrap_err().kind(), std::io::ErrorKind::NotConnected);
+        assert_eq!(std::fs::read_to_string(&log_path).unwrap(), "Hello to stdout!");
+    }
+}
+
+/// Redirects `stderr` to a file and then `stdout` to `stderr`, and validates that
+/// output to both streams reaches the file.
+fn stdout_to_stderr() {
+    // Arrange
+    let tempdir = tempfile::tempdir().unwrap();
+    let log_path = tempdir.path().join("log.txt");
+    let log_file = File::create(&log_path).unwrap();
+    stderr().redirect(&log_file).unwrap();
+
+    // Act
+    redirect_stdout_to_stderr().unwrap();
+    print!("Hello to stdout!");
+    stdout().flush().unwrap();
+    eprint!("Hello to stderr!");
+    stderr().flush().unwrap();
+
+    // Assert
+    assert_eq!(std::fs::read_to_string(&log_path).unwrap(), "Hello to stdout!Hello to stderr!");
+}
+
+/// Redirects `stdout` to a file and then `stderr` to `stdout`, and validates that
+/// output to both streams reaches the file.
+fn stderr_to_stdout() {
+    // Arrange
+    let tempdir = tempfile::tempdir().unwrap();
+    let log_path = tempdir.path().join("log.txt");
+    let log_file = File::create(&log_path).unwrap();
+    stdout().redirect(&log_file).unwrap();
+
+    // Act
+    redirect_stderr_to_stdout().unwrap();
+    print!("Hello to stdout!");
+    stdout().flush().unwrap();
+    eprint!("Hello to stderr!");
+    stderr().flush().unwrap();
+
+    // Assert
+    assert_eq!(std::fs::read_to_string(&log_path).unwrap(), "Hello to stdout!Hello to stderr!");
+}
+
+/// Redirects `stdin` to read bytes fed by a background thread and validates that
+/// reading a line from `stdin` yields those bytes.
+#[cfg(unix)]
+fn stdin_from_bytes() {
+    // Act
+    let handle = redirect_stdin_from_bytes(b"hello\n").unwrap();
+    let line = stdin().lines().next().unwrap().unwrap();
+
+    // Assert
+    handle.join().unwrap();
+    assert_eq!(line, "hello");
+}
+
+/// Redirects only `stdout` to a file with pre-existing content, truncating it, after checking
+/// that `create_new` refuses the existing file. Validates that `stderr` is left untouched.
+fn std_to_path_with_options() {
+    // Arrange
+    let tempdir = tempfile::tempdir().unwrap();
+    let log_path = tempdir.path().join("log.txt");
+    std::fs::write(&log_path, "Old content!").unwrap();
+    let exclusive = RedirectOptions { create_new: true, ..Default::default() };
+    let truncating = RedirectOptions { append: false, truncate: true, include_stderr: false, ..Default::default() };
+
+    // Act
+    let err = redirect_std_to_path_with_options(&log_path, &exclusive).unwrap_err();
+    redirect_std_to_path_with_options(&log_path, &truncating).unwrap();
+    print!("Hello to stdout!");
+    stdout().flush().unwrap();
+    eprint!("Hello to stderr!");
+    stderr().flush().unwrap();
+
+    // Assert
+    assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+    assert_eq!(std::fs::read_to_string(&log_path).unwrap(), "Hello to stdout!");
+}
+
+/// Redirects only `stdin` to an existing file without creating or appending and validates
+/// that reading `stdin` yields the file content.
+fn stdin_from_path_with_options() {
+    // Arrange
+    let tempdir = tempfile::tempdir().unwrap();
+    let input_path = tempdir.path().join("input.txt");
+    std::fs::write(&input_path, "Hello from a file!\n").unwrap();
+    let opts = RedirectOptions { append: false, create: false, include_stdin: true, include_stdout: false, include_stderr: false, ..Default::default() };
+
+    // Act
+    redirect_std_to_path_with_options(&input_path, &opts).unwrap();
+    let mut src_contents = String::new();
+    stdin().read_to_string(&mut src_contents).unwrap();
+
+    // Assert
+    assert_eq!(src_contents, "Hello from a file!\n");
+}
+
+/// Redirects `stdout` to an in-memory file, seals it against writes, and validates
+/// the bytes read back from it.
+#[cfg(all(target_os = "linux", feature = "linux-memfd"))]
+fn stdout_to_memfd() {
+    use io_redirect::unix::{create_memfd, memfd_seal};
+    use std::io::{Seek, SeekFrom};
+
+    // Arrange
+    let mut memfd = create_memfd(c"stdout", libc::MFD_CLOEXEC | libc::MFD_ALLOW_SEALING).unwrap();
+
+    // Act
+    stdout().redirect(&memfd).unwrap();
+    print!("Hello to memory!");
+    stdout().flush().unwrap();
+    memfd_seal(&memfd, libc::F_SEAL_WRITE as u32).unwrap();
+    let mut captured = String::new();
+    memfd.seek(SeekFrom::Start(0)).unwrap();
+    memfd.read_to_string(&mut captured).unwrap();
+
+    // Assert
+    assert_eq!(captured, "Hello to memory!");
+}
+
+/// Redirects `stdout` and `stderr` to separate files and validates that each file only
+/// contains the output of its own stream. Also validates that a bad `stderr` path fails
+/// before `stdout` is redirected.
+fn std_pair() {
+    // Arrange
+    let tempdir = tempfile::tempdir().unwrap();
+    let stdout_path = tempdir.path().join("stdout.txt");
+    let stderr_path = tempdir.path().join("stderr.txt");
+    let bad_path = tempdir.path().join("no_such_dir").join("stderr.txt");
+
+    // Act
+    let err = redirect_std_pair(&stdout_path, &bad_path, true).unwrap_err();
+    redirect_std_pair(&stdout_path, &stderr_path, true).unwrap();
+    print!("Hello to stdout!");
+    stdout().flush().unwrap();
+    eprint!("Hello to stderr!");
+    stderr().flush().unwrap();
+
+    // Assert
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    assert_eq!(std::fs::read_to_string(&stdout_path).unwrap(), "Hello to stdout!");
+    assert_eq!(std::fs::read_to_string(&stderr_path).unwrap(), "Hello to stderr!");
+}
+
+/// Redirects `stdout` to the client end of a named pipe and validates that a thread reading
+/// the server end receives what was printed.
+#[cfg(all(windows, feature = "windows-sys"))]
+fn stdout_to_named_pipe() {
+    use io_redirect::{clone_descriptor, create_named_pipe_pair};
+
+    // Arrange
+    let (mut server, client) = create_named_pipe_pair(&format!("io-redirect-selftest-{}", std::process::id())).unwrap();
+    let reader = std::thread::spawn(move || {
+        let mut received = String::new();
+        server.read_to_string(&mut received).unwrap();
+        received
+    });
+    let original_stdout = clone_descriptor(&stdout()).unwrap();
+
+    // Act
+    stdout().redirect(&client).unwrap();
+    print!("Hello to named pipe!");
+    stdout().flush().unwrap();
+    stdout().redirect(&original_stdout).unwrap();
+    drop(client);
+
+    // Assert
+    assert_eq!(reader.join().unwrap(), "Hello to named pipe!");
+}
+
+/// Intercepts `stdout` with a callback counting newlines and validates that the callback saw
+/// every line and that the output still reached the original destination.
+#[cfg(unix)]
+fn intercept_stdout() {
+    use io_redirect::intercept_redirect;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    // Arrange
+    let tempdir = tempfile::tempdir().unwrap();
+    let original_path = tempdir.path().join("original.txt");
+    stdout().redirect(original_path.as_path()).unwrap();
+    let newlines = Arc::new(AtomicUsize::new(0));
+    let counter = newlines.clone();
+
+    // Act
+    let intercept = intercept_redirect(stdout().as_raw_fd(), move |chunk| {
+        counter.fetch_add(chunk.iter().filter(|&&b| b == b'\n').count(), Ordering::SeqCst);
+    }).unwrap();
+    println!("first line");
+    println!("second line");
+    println!("third line");
+    drop(intercept);
+
+    // Assert
+    assert_eq!(newlines.load(Ordering::SeqCst), 3);
+    assert_eq!(std::fs::read_to_string(&original_path).unwrap(), "first line\nsecond line\nthird line\n");
+}
+
+/// Saves `stdout` and `stderr`, redirects them to a file and restores them explicitly, then
+/// validates that only the writes made while redirected reached the file.
+#[cfg(unix)]
+fn save_restore_std() {
+    use io_redirect::{restore_std, save_std};
+
+    // Arrange
+    let tempdir = tempfile::tempdir().unwrap();
+    let log_path = tempdir.path().join("log.txt");
+
+    // Act
+    let saved = save_std().unwrap();
+    redirect_std_to_path(&log_path, true).unwrap();
+    print!("Hello to file!");
+    stdout().flush().unwrap();
+    eprint!("Hello to file!");
+    restore_std(saved).unwrap();
+    print!("Hello to stdout!");
+    stdout().flush().unwrap();
+    eprint!("Hello to stderr!");
+
+    // Assert
+    assert_eq!(std::fs::read_to_string(&log_path).unwrap(), "Hello to file!Hello to file!");
+}
+
+/// Redirects `stdout` to a new file and validates that redirecting to the same path again
+/// fails with `AlreadyExists` without touching the file.
+fn std_to_path_exclusive() {
+    // Arrange
+    let tempdir = tempfile::tempdir().unwrap();
+    let log_path = tempdir.path().join("audit.log");
+
+    // Act
+    redirect_std_to_path_exclusive(&log_path, false).unwrap();
+    print!("Hello to audit log!");
+    stdout().flush().unwrap();
+    let second = redirect_std_to_path_exclusive(&log_path, false);
+
+    // Assert
+    assert_eq!(second.unwrap_err().kind(), std::io::ErrorKind::AlreadyExists);
+    assert_eq!(std::fs::read_to_string(&log_path).unwrap(), "Hello to audit log!");
+}
+
+/// Redirects `stdout` to a file in a missing directory and `stderr` to the null device with a
+/// `RedirectBuilder`, then validates the file and that dropping the guard restores both streams.
+#[cfg(unix)]
+fn builder() {
+    use io_redirect::{RedirectBuilder, RedirectTarget};
+
+    // Arrange
+    let tempdir = tempfile::tempdir().unwrap();
+    let log_path = tempdir.path().join("logs").join("out.txt");
+
+    // Act
+    let applied = RedirectBuilder::new()
+        .stdout_to(log_path.as_path())
+        .stderr_to(RedirectTarget::Null)
+        .create_parents(true)
+        .apply()
+        .unwrap();
+    print!("Hello to file!");
+    stdout().flush().unwrap();
+    eprint!("Hello to nowhere!");
+    drop(applied);
+    print!("Hello to stdout!");
+    stdout().flush().unwrap();
+    eprint!("Hello to stderr!");
+
+    // Assert
+    assert_eq!(std::fs::read_to_string(&log_path).unwrap(), "Hello to file!");
+}
+
+/// Redirects `stdin` to read from a reader and then from a string, and validates that reading
+/// `stdin` yields their contents.
+fn stdin_from_reader() {
+    use io_redirect::{redirect_stdin_from_reader, redirect_stdin_from_string};
+    use std::io::Cursor;
+
+    // Act
+    let handle = redirect_stdin_from_reader(Cursor::new(b"first\nsecond\n".to_vec())).unwrap();
+    let mut from_reader = String::new();
+    stdin().lock().read_to_string(&mut from_reader).unwrap();
+    handle.join().unwrap();
+    let handle = redirect_stdin_from_string("third\n").unwrap();
+    let mut from_string = String::new();
+    stdin().lock().read_to_string(&mut from_string).unwrap();
+    handle.join().unwrap();
+
+    // Assert
+    assert_eq!(from_reader, "first\nsecond\n");
+    assert_eq!(from_string, "third\n");
+}
+
+/// Redirects `stdout` to syslog and validates that dropping the handle returns promptly and
+/// restores `stdout`, whose output the test harness checks.
+#[cfg(unix)]
+fn stdout_to_syslog() {
+    use io_redirect::{redirect_std_to_syslog, SyslogFacility};
+
+    // Act
+    let handle = redirect_std_to_syslog(c"io-redirect-selftest", SyslogFacility::User, true, false).unwrap();
+    println!("Hello to syslog!");
+    drop(handle);
+    print!("Hello to stdout!");
+}
+
+/// Redirects `stdout` and `stderr` to a journal socket bound by this process and validates
+/// the native protocol messages received on it.
+#[cfg(all(target_os = "linux", feature = "systemd"))]
+fn std_to_journald() {
+    use io_redirect::redirect_std_to_journald_socket;
+    use std::os::unix::net::UnixDatagram;
+
+    // Arrange
+    let tempdir = tempfile::tempdir().unwrap();
+    let socket_path = tempdir.path().join("journal.sock");
+    let journal = UnixDatagram::bind(&socket_path).unwrap();
+
+    // Act
+    let handle = redirect_std_to_journald_socket(&socket_path).unwrap();
+    println!("Hello to journal!");
+    drop(handle);
+    eprintln!("Hello to stderr!");
+    let mut buf = [0u8; 256];
+    let len = journal.recv(&mut buf).unwrap();
+
+    // Assert
+    assert_eq!(&buf[..len], b"PRIORITY=6\nMESSAGE=Hello to journal!\n");
+}
+
+/// Opens a few files, closes every descriptor above `stderr` and validates that only the
+/// standard streams remain open.
+#[cfg(unix)]
+fn close_inherited_fds() {
+    use io_redirect::unix;
+
+    // Arrange
+    let tempdir = tempfile::tempdir().unwrap();
+    let files: Vec<File> = (0..3).map(|i| File::create(tempdir.path().join(format!("{i}.txt"))).unwrap()).collect();
+
+    // Act
+    unix::close_inherited_fds().unwrap();
+    std::mem::forget(files);
+
+    // Assert
+    assert!(unix::list_open_fds().unwrap().into_iter().all(|fd| fd <= 2));
+}
+
+/// Redirects `stdout` to one end of a Unix socket pair and validates that the output arrives
+/// at the other end.
+#[cfg(unix)]
+fn stdout_to_unix_socket() {
+    use std::os::unix::net::UnixStream;
+
+    // Arrange
+    let expected = "Hello to socket!";
+    let (write_end, mut read_end) = UnixStream::pair().unwrap();
+    let reader = std::thread::spawn(move || {
+        let mut received = vec![0u8; expected.len()];
+        read_end.read_exact(&mut received).unwrap();
+        received
+    });
+
+    // Act
+    stdout().redirect(&write_end).unwrap();
+    print!("{expected}");
+    stdout().flush().unwrap();
+
+    // Assert
+    assert_eq!(reader.join().unwrap(), expected.as_bytes());
+}
+
+/// Redirects `stdout` to a TCP connection and validates that the output is received by the
+/// accepting side.
+#[cfg(unix)]
+fn stdout_to_tcp_stream() {
+    use std::net::{TcpListener, TcpStream};
+
+    // Arrange
+    let expected = "Hello to TCP!";
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (mut accepted, _) = listener.accept().unwrap();
+    let reader = std::thread::spawn(move || {
+        let mut received = vec![0u8; expected.len()];
+        accepted.read_exact(&mut received).unwrap();
+        received
+    });
+
+    // Act
+    stdout().redirect(&client).unwrap();
+    print!("{expected}");
+    stdout().flush().unwrap();
+
+    // Assert
+    assert_eq!(reader.join().unwrap(), expected.as_bytes());
+}
+
+/// Redirects `stdin` to one file and then another, and validates that each read yields the
+/// content of the file `stdin` was redirected to last.
+fn stdin_redirected_twice() {
+    // Arrange
+    let tempdir = tempfile::tempdir().unwrap();
+    std::fs::write(tempdir.path().join("first.txt"), "Hello from the first file!\n").unwrap();
+    std::fs::write(tempdir.path().join("second.txt"), "Hello from the second file!\n").unwrap();
+    let first = File::open(tempdir.path().join("first.txt")).unwrap();
+    let second = File::open(tempdir.path().join("second.txt")).unwrap();
+
+    // Act
+    stdin().redirect(&first).unwrap();
+    let mut first_contents = String::new();
+    stdin().read_to_string(&mut first_contents).unwrap();
+    stdin().redirect(&second).unwrap();
+    let mut second_contents = String::new();
+    stdin().read_to_string(&mut second_contents).unwrap();
+
+    // Assert
+    assert_eq!(first_contents, "Hello from the first file!\n");
+    assert_eq!(second_contents, "Hello from the second file!\n");
+}
+
+/// Redirects `stdout` and `stderr` to a file until the returned guard is dropped, and validates
+/// that only the output written in between reaches the file.
+#[cfg(unix)]
+fn std_to_path_guarded() {
+    use io_redirect::redirect_std_to_path_guarded;
+
+    // Arrange
+    let tempdir = tempfile::tempdir().unwrap();
+    let log_path = tempdir.path().join("log.txt");
+
+    // Act
+    let guard = redirect_std_to_path_guarded(&log_path, true).unwrap();
+    print!("Hello to file!");
+    stdout().flush().unwrap();
+    eprint!("Hello to file again!");
+    drop(guard);
+    print!("Hello to stdout!");
+    stdout().flush().unwrap();
+    eprint!("Hello to stderr!");
+
+    // Assert
+    assert_eq!(std::fs::read_to_string(&log_path).unwrap(), "Hello to file!Hello to file again!");
+}
+
+/// Redirects `stdout` and `stderr` to a file and validates that both are reported to refer to it.
+fn redirect_active() {
+    use io_redirect::is_redirect_active;
+
+    // Arrange
+    let tempdir = tempfile::tempdir().unwrap();
+    let log_path = tempdir.path().join("log.txt");
+    let other_path = tempdir.path().join("other.txt");
+    File::create(&other_path).unwrap();
+
+    // Act
+    redirect_std_to_path(&log_path, true).unwrap();
+
+    // Assert
+    assert!(is_redirect_active(&stdout(), &log_path).unwrap());
+    assert!(is_redirect_active(&stderr(), &log_path).unwrap());
+    assert!(!is_redirect_active(&stdout(), &other_path).unwrap());
+}
+
+/// Captures what a closure writes to the raw `stdout` descriptor and validates that the captured
+/// bytes and the result of the closure are returned, and that `stdout` is restored afterwards.
+#[cfg(unix)]
+fn capture_fd_output() {
+    // Arrange
+    let expected = "Hello to the capture!";
+
+    // Act
+    let (captured, written) = io_redirect::capture_fd_output(libc::STDOUT_FILENO, || unsafe { libc::write(libc::STDOUT_FILENO, expected.as_ptr().cast(), expected.len()) }).unwrap();
+    print!("Hello to stdout!");
+    stdout().flush().unwrap();
+
+    // Assert
+    assert_eq!(captured, expected.as_bytes());
+    assert_eq!(written, expected.len() as isize);
+}
+
+/// Redirects `stdout` and `stderr` to a new file under a restrictive umask, and validates the
+/// permissions of the file and that the previous umask is restored.
+#[cfg(unix)]
+fn std_to_path_with_umask() {
+    use io_redirect::redirect_std_to_path_with_umask;
+    use std::os::unix::fs::PermissionsExt;
+
+    // Arrange
+    let tempdir = tempfile::tempdir().unwrap();
+    let log_path = tempdir.path().join("log.txt");
+    unsafe { libc::umask(0o022) };
+
+    // Act
+    redirect_std_to_path_with_umask(&log_path, true, 0o077).unwrap();
+    print!("Hello to private file!");
+    stdout().flush().unwrap();
+
+    // Assert
+    assert_eq!(unsafe { libc::umask(0o022) }, 0o022);
+    assert_eq!(std::fs::metadata(&log_path).unwrap().permissions().mode() & 0o777, 0o600);
+    assert_eq!(std::fs::read_to_string(&log_path).unwrap(), "Hello to private file!");
+}
+
+/// Redirects `stdout` to the file given as the second argument and replaces this process with
+/// `echo hello`. Only returns, and fails, if the exec fails.
+#[cfg(unix)]
+fn redirect_and_exec() {
+    use io_redirect::unix::redirect_and_exec;
+    use std::path::Path;
+
+    // Arrange
+    let log_path = std::env::args().nth(2).expect("missing log path argument");
+    let log_file = File::create(log_path).unwrap();
+
+    // Act
+    let error = redirect_and_exec(Path::new("/bin/echo"), &["hello".into()], &[], &[(stdout().as_raw_fd(), log_file.as_raw_fd())]);
+
+    // Assert
+    panic!("exec failed: {error}");
+}
+
+/// Redirects `stdin`, `stdout` and `stderr` to files named by environment variables, and validates
+/// that unset variables leave the streams alone.
+fn std_from_env() {
+    use io_redirect::{redirect_std_to_env, redirect_stdin_from_env};
+
+    // Arrange
+    let tempdir = tempfile::tempdir().unwrap();
+    let input_path = tempdir.path().join("input.txt");
+    let log_path = tempdir.path().join("log.txt");
+    std::fs::write(&input_path, "Hello from the environment!\n").unwrap();
+    // `set_var` is not thread-safe, but this scenario runs on the main thread only.
+    std::env::set_var("SELFTEST_STDIN", &input_path);
+    std::env::set_var("SELFTEST_STDOUT", &log_path);
+    std::env::set_var("SELFTEST_STDERR", &log_path);
+    std::env::remove_var("SELFTEST_UNSET");
+
+    // Act
+    let unset_stdin = redirect_stdin_from_env("SELFTEST_UNSET").unwrap();
+    let unset_std = redirect_std_to_env("SELFTEST_UNSET", "SELFTEST_UNSET", true).unwrap();
+    let stdin_redirected = redirect_stdin_from_env("SELFTEST_STDIN").unwrap();
+    let std_redirected = redirect_std_to_env("SELFTEST_STDOUT", "SELFTEST_STDERR", true).unwrap();
+    let mut src_contents = String::new();
+    stdin().read_to_string(&mut src_contents).unwrap();
+    print!("Hello to stdout!");
+    stdout().flush().unwrap();
+    eprint!("Hello to stderr!");
+
+    // Assert
+    assert_eq!(unset_stdin, None);
+    assert_eq!(unset_std, 0);
+    assert_eq!(stdin_redirected, Some(()));
+    assert_eq!(std_redirected, 2);
+    assert_eq!(src_contents, "Hello from the environment!\n");
+    assert_eq!(std::fs::read_to_string(&log_path).unwrap(), "Hello to stdout!Hello to stderr!");
+}
+
+/// Installs a `SIGCHLD` handler without `SA_RESTART`, then redirects `stdout` repeatedly while
+/// another thread keeps signalling the main thread, and validates that no redirect fails. This
+/// is a smoke test: whether a signal lands inside `dup2` depends on timing, so it exercises the
+/// `EINTR` retry only on systems and runs where `dup2` actually gets interrupted.
+#[cfg(unix)]
+fn redirect_while_signalled() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    extern "C" fn on_signal(_: libc::c_int) {}
+
+    // Arrange
+    let tempdir = tempfile::tempdir().unwrap();
+    let path = tempdir.path().join("stdout.txt");
+    let dst = File::create(&path).unwrap();
+    let expected = "Hello after signals!";
+    let mut action: libc::sigaction = unsafe { std::mem::zeroed() };
+    action.sa_sigaction = on_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+    assert_eq!(unsafe { libc::sigaction(libc::SIGCHLD, &action, std::ptr::null_mut()) }, 0);
+    let main_thread = unsafe { libc::pthread_self() } as usize;
+    let stop = Arc::new(AtomicBool::new(false));
+    let signaller = {
+        let stop = stop.clone();
+        std::thread::spawn(move || {
+            while !stop.load(Ordering::Relaxed) {
+                unsafe { libc::pthread_kill(main_thread as libc::pthread_t, libc::SIGCHLD) };
+                std::thread::yield_now();
+            }
+        })
+    };
+
+    // Act
+    for _ in 0..10_000 {
+        stdout().redirect(&dst).unwrap();
+    }
+    stop.store(true, Ordering::Relaxed);
+    signaller.join().unwrap();
+    print!("{expected}");
+    stdout().flush().unwrap();
+
+    // Assert
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), expected);
+}
+
+/// Redirects `stdout` and `stderr` to two files, swaps their targets and validates that each
+/// stream then writes to the other file.
+#[cfg(unix)]
+fn swap_std_pair() {
+    use io_redirect::unix;
+
+    // Arrange
+    let tempdir = tempfile::tempdir().unwrap();
+    let stdout_path = tempdir.path().join("stdout.txt");
+    let stderr_path = tempdir.path().join("stderr.txt");
+    stdout().redirect(stdout_path.as_path()).unwrap();
+    stderr().redirect(stderr_path.as_path()).unwrap();
+
+    // Act
+    unix::redirect_pair_swap(stdout().as_raw_fd(), stderr().as_raw_fd()).unwrap();
+    print!("Hello from stdout!");
+    stdout().flush().unwrap();
+    eprint!("Hello from stderr!");
+
+    // Assert
+    assert_eq!(std::fs::read_to_string(&stdout_path).unwrap(), "Hello from stderr!");
+    assert_eq!(std::fs::read_to_string(&stderr_path).unwrap(), "Hello from stdout!");
+}
+
+/// Mirrors `stderr` to a log file and validates that the output reaches the file. The output on
+/// the original `stderr` is validated by the caller.
+#[cfg(unix)]
+fn stderr_tee_to_path() {
+    use io_redirect::redirect_stderr_tee;
+
+    // Arrange
+    let tempdir = tempfile::tempdir().unwrap();
+    let log_path = tempdir.path().join("error.log");
+    std::fs::write(&log_path, "stale").unwrap();
+
+    // Act
+    let tee = redirect_stderr_tee(&log_path, false).unwrap();
+    eprintln!("Hello to stderr!");
+    drop(tee);
+
+    // Assert
+    assert_eq!(std::fs::read_to_string(&log_path).unwrap(), "Hello to stderr!\n");
+}
+
+/// Redirects `stdout` and `stderr` with the fallible variant of
+/// `redirect_std_to_path_with_options` and validates that both reach the file.
+fn try_std_to_path_with_options() {
+    use io_redirect::try_redirect_std_to_path_with_options;
+
+    // Arrange
+    let tempdir = tempfile::tempdir().unwrap();
+    let path = tempdir.path().join("std.txt");
+    let missing = tempdir.path().join("missing").join("std.txt");
+    let opts = RedirectOptions { append: true, ..Default::default() };
+
+    // Act
+    let open_failed = try_redirect_std_to_path_with_options(&missing, &opts);
+    try_redirect_std_to_path_with_options(&path, &opts).unwrap();
+    print!("Hello to stdout!");
+    stdout().flush().unwrap();
+    eprint!("Hello to stderr!");
+
+    // Assert
+    assert!(matches!(open_failed, Err(io_redirect::RedirectError::OpenDestinationFailed { .. })));
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "Hello to stdout!Hello to stderr!");
+}
+
+/// Redirects an opened file to `stdout` and validates that writing to the file reaches `stdout`
+/// instead. The output on `stdout` is validated by the caller.
+fn file_to_stdout() {
+    // Arrange
+    let tempdir = tempfile::tempdir().unwrap();
+    let file_path = tempdir.path().join("file.txt");
+    let mut file = File::create(&file_path).unwrap();
+
+    // Act
+    file.redirect(&stdout()).unwrap();
+    file.write_all(b"Hello to stdout!").unwrap();
+    file.flush().unwrap();
+
+    // Assert
+    assert_eq!(std::fs::read_to_string(&file_path).unwrap(), "");
+}
+
+/// Redirects only `stderr` to an opened file and validates that `stdout` is left untouched. The
+/// output on `stdout` is validated by the caller.
+fn stderr_to_file() {
+    // Arrange
+    let tempdir = tempfile::tempdir().unwrap();
+    let log_path = tempdir.path().join("log.txt");
+    let log_file = File::create(&log_path).unwrap();
+
+    // Act
+    stderr().redirect(&log_file).unwrap();
+    print!("Hello to stdout!");
+    stdout().flush().unwrap();
+    eprint!("Hello to stderr!");
+
+    // Assert
+    assert_eq!(std::fs::read_to_string(&log_path).unwrap(), "Hello to stderr!");
+}
+
+/// Redirects a range of descriptor numbers to a file, writes through each of them, closes the
+/// range and validates that the writes reached the file and the numbers are closed. This runs in
+/// its own process, as it takes over descriptor numbers that nothing else must be using.
+#[cfg(unix)]
+fn fd_range() {
+    use io_redirect::unix;
+    use std::mem::ManuallyDrop;
+    use std::os::fd::FromRawFd;
+
+    // Arrange
+    let tempdir = tempfile::tempdir().unwrap();
+    let log_path = tempdir.path().join("log.txt");
+    let log_file = File::create(&log_path).unwrap();
+    let (start, end) = (1000, 1002);
+
+    // Act
+    unix::redirect_fd_range(start, end, log_file.as_raw_fd()).unwrap();
+    for fd in start..=end {
+        ManuallyDrop::new(unsafe { File::from_raw_fd(fd) }).write_all(b"x").unwrap();
+    }
+    unix::close_fd_range(start, end).unwrap();
+
+    // Assert
+    assert_eq!(std::fs::read_to_string(&log_path).unwrap(), "xxx");
+    for fd in start..=end {
+        assert_eq!(unsafe { libc::fcntl(fd, libc::F_GETFD) }, -1);
+    }
+    assert_eq!(unix::close_fd_range(end, start).unwrap_err().kind(), std::io::ErrorKind::InvalidInput);
+}
+
+/// Redirects `stdout` to a path read from an environment variable as an `OsString` and validates
+/// that the output reaches the file.
+fn stdout_to_os_string_from_env() {
+    // Arrange
+    let tempdir = tempfile::tempdir().unwrap();
+    let log_path = tempdir.path().join("log.txt");
+    // `set_var` is not thread-safe, but this scenario runs on the main thread only.
+    std::env::set_var("SELFTEST_DESTINATION", &log_path);
+    let destination = std::env::var_os("SELFTEST_DESTINATION").unwrap();
+
+    // Act
+    stdout().redirect(&*destination).unwrap();
+    print!("Hello to stdout!");
+    stdout().flush().unwrap();
+
+    // Assert
+    assert_eq!(std::fs::read_to_string(&log_path).unwrap(), "Hello to stdout!");
 }
\ No newline at end of file
@@ -6,13 +6,42 @@
 //! access without changing user code.
 //!
 //! ## Platform Support
-//! | Platform  | Required Features | File to File | Stdout/Stderr to File | Any FD to Any FD |
-//! | -         | -                 | -            | -                     | -                |
-//! | Unix-like | `libc_on_unix`    | Yes          | Yes                   | Yes              |
-//! | Windows   | `windows-sys`     | No           | Yes                   | No               |
-//! | Windows   | `libc_on_windows` | Yes          | No                    | No               |
+//! | Platform  | Required Features | File to File | Stdin/Stdout/Stderr to File | Any FD to Any FD |
+//! | -         | -                 | -            | -                           | -                |
+//! | Unix-like | `libc_on_unix`    | Yes          | Yes                         | Yes              |
+//! | Windows   | `windows-sys`     | Yes          | Yes                         | Partial          |
+//! | Windows   | `libc_on_windows` | Yes          | No                          | No               |
 //!
-//! All features are enabled by default on all platforms.
+//! On Windows, `File`, `OwnedHandle` and the standard streams can be redirected to any handle, e.g.
+//! a `File` to `Stdout`. Other handle types cannot be sources: a blanket implementation for every
+//! `AsRawHandle` type would overlap with the standard stream implementations, which have to go
+//! through `SetStdHandle`, and replacing the handle inside an arbitrary type is not sound.
+//!
+//! Sockets such as `std::net::TcpStream` are not supported on Windows. They implement
+//! `AsRawSocket` rather than `AsRawHandle`, and Winsock does not guarantee that a `SOCKET` is a
+//! kernel handle usable with `WriteFile`, so console output written to one may be lost. Use a named
+//! pipe, see `create_named_pipe_pair`, to forward output to another process instead.
+//!
+//! The features above are enabled by default on all platforms, together with `std`. On Windows, File
+//! to File redirection uses the `libc_on_windows` implementation whenever that feature is enabled.
+//!
+//! The `std` feature provides the path-based and standard stream convenience functions, and on
+//! Windows the `Stdin`/`Stdout`/`Stderr` implementations. Disabling it trims the crate down to
+//! descriptor-based redirection for minimal targets. The crate itself still links the standard library.
+//!
+//! Optional features:
+//! - `raw-fd`: `redirect_raw_fd` for raw descriptor numbers.
+//! - `tokio`: `AsyncRedirectable` for redirecting from async code on Unix-like platforms.
+//! - `linux-memfd`: `unix::create_memfd` and `unix::memfd_seal` for in-memory destinations on Linux,
+//!   also used by `redirect_to_cursor` instead of a pipe.
+//! - `linux-splice`: `unix::splice_fd` and `unix::splice_loop` for moving data between pipes on Linux.
+//! - `scm-rights`: `unix::send_fd` and `unix::recv_fd` for passing descriptors over Unix domain sockets.
+//! - `systemd`: `redirect_std_to_journald` for sending stdout and stderr to the systemd journal on Linux.
+//! - `linux-pidfd`: `unix::borrow_fd_from_process` for duplicating descriptors of other processes on Linux.
+//! - `log`: `trace!` records of every descriptor redirection and opened destination, and `warn!`
+//!   records of failures, with the module path as the target.
+//! - `tracing`: `redirect_fd_to_fd` spans with `src_fd` and `dst_fd` fields and `open_destination`
+//!   spans with a `path` field around descriptor redirections and opened destinations.
 //!
 //! <div class="warning">
 //! On Windows, `Redirectable<T>` trait accepts any `T` that can be converted into a handle.
@@ -23,6 +52,21 @@
 //! ## Usage
 //! For a more detailed example, see the `selftest` executable.
 //!
+//! ### Temporarily Redirect Standard Streams to a File
+//! ```no_run
+//! # #[cfg(unix)] {
+//! use io_redirect::redirect_std_to_path_guarded;
+//!
+//! // redirect stdout and stderr until the guard is dropped, e.g. to capture output in tests
+//! let guard = redirect_std_to_path_guarded("captured.log", true).unwrap();
+//! println!("goes to captured.log");
+//! eprintln!("so does this");
+//! drop(guard);
+//!
+//! println!("back on the original stdout");
+//! # }
+//! ```
+//!
 //! ### File to File
 //! ```no_run
 //! use io_redirect::Redirectable;
@@ -43,7 +87,7 @@
 //! let some_path = PathBuf::from("/dev/kmsg");
 //!
 //! // redirect both stdout and stderr to the same file
-//! redirect_std_to_path(some_path.as_path(), true).unwrap();
+//! redirect_std_to_path(&some_path, true).unwrap();
 //!
 //! // or just one stream
 //! # use io_redirect::Redirectable;
@@ -51,14 +95,17 @@
 //! ```
 //!
 //! ## Notes and Caveats
-//! - **Resource Management**: Avoid using `Redirectable<Path>::redirect(...)` multiple times on the same entity as each call will leak a file descriptor. `Redirectable<File>` does not suffer from the same.
+//! - **Resource Management**: On Windows, avoid using `Redirectable<Path>::redirect(...)` multiple times on the same entity as each call will leak a handle. Use `redirect_to_path_owning(...)` to control when the handle is closed instead. `Redirectable<File>` does not suffer from the same.
+//! - **Error Context**: `Redirectable` reports plain `io::Error`s. Use `FallibleRedirectable::try_redirect(...)` instead to get a `RedirectError` that tells which operation failed.
 //! - **OS-Specific Behavior**: Not all features may function identically across platforms; ensure
 //!   feature flags match the intended target for compilation.
 //!
 
+#![allow(clippy::needless_return)]
+#![cfg_attr(test, allow(clippy::suspicious_open_options))]
+
 use std::io;
-use std::io::{Stdout, Stderr};
-use std::fs::{File, OpenOptions};
+use std::fs::File;
 
 /// A trait to represent entities that can have their I/O redirected to a specified target.
 ///
@@ -96,7 +143,117 @@ pub trait Redirectable<T: ?Sized>
     fn redirect(&mut self, destination: &T) -> io::Result<()>;
 }
 
-#[cfg(any(unix))]
+/// A counterpart of [`Redirectable`] that reports which operation failed.
+///
+/// # Type Parameters
+/// - `T`: The type of the destination, as in [`Redirectable`].
+pub trait FallibleRedirectable<T: ?Sized>
+{
+    /// Redirects I/O to a specified destination.
+    ///
+    /// # Parameters
+    /// - `destination`: A reference to the target destination.
+    ///
+    /// # Returns
+    /// - `Result<(), RedirectError>`: `Ok` if successful, `Err` describing the failed operation otherwise.
+    fn try_redirect(&mut self, destination: &T) -> Result<(), RedirectError>;
+}
+
+/// Redirects `source` to `dest` only if `condition` is true.
+///
+/// # Parameters
+/// - `source`: The entity to redirect.
+/// - `dest`: A reference to the target destination.
+/// - `condition`: Whether to redirect at all.
+///
+/// # Returns
+/// - `io::Result<()>`: `Ok` if successful or if `condition` is false, `Err` otherwise.
+///
+/// # Examples
+/// ```no_run
+/// use io_redirect::redirect_conditional;
+///
+/// let foreground = std::env::args().any(|arg| arg == "--foreground");
+/// let log_file = std::fs::File::create("daemon.log").unwrap();
+/// redirect_conditional(&mut std::io::stdout(), &log_file, !foreground).unwrap();
+/// ```
+pub fn redirect_conditional<T: Redirectable<U>, U: ?Sized>(source: &mut T, dest: &U, condition: bool) -> io::Result<()> {
+    if !condition {
+        return Ok(());
+    }
+    return source.redirect(dest);
+}
+
+mod error
+{
+    use super::*;
+    use std::fmt;
+    use std::path::PathBuf;
+
+    /// An error raised by [`FallibleRedirectable`], carrying the context of the failed operation.
+    #[derive(Debug)]
+    pub enum RedirectError
+    {
+        /// Duplicating `dst_fd` onto `src_fd` failed.
+        DupFailed { src_fd: i32, dst_fd: i32, source: io::Error },
+        /// Opening the destination at `path` failed.
+        OpenDestinationFailed { path: PathBuf, source: io::Error },
+        /// Redirecting several entities stopped at `failed_at` after `completed` were redirected.
+        PartialRedirect { completed: Vec<String>, failed_at: String, source: io::Error },
+        /// Any other I/O error.
+        Io(io::Error),
+    }
+
+    impl RedirectError {
+        /// Returns the underlying I/O error.
+        pub fn io_error(&self) -> &io::Error {
+            return match self {
+                RedirectError::DupFailed { source, .. } => source,
+                RedirectError::OpenDestinationFailed { source, .. } => source,
+                RedirectError::PartialRedirect { source, .. } => source,
+                RedirectError::Io(source) => source,
+            };
+        }
+    }
+
+    impl fmt::Display for RedirectError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            return match self {
+                RedirectError::DupFailed { src_fd, dst_fd, source } => write!(f, "redirect fd {src_fd} → fd {dst_fd} failed: {source}"),
+                RedirectError::OpenDestinationFailed { path, source } => write!(f, "open {path:?} for redirect failed: {source}"),
+                RedirectError::PartialRedirect { completed, failed_at, source } => {
+                    write!(f, "failed to redirect {failed_at} after redirecting [{}]: {source}", completed.join(", "))
+                }
+                RedirectError::Io(source) => write!(f, "{source}"),
+            };
+        }
+    }
+
+    impl std::error::Error for RedirectError {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            return Some(self.io_error());
+        }
+    }
+
+    impl From<io::Error> for RedirectError {
+        fn from(error: io::Error) -> RedirectError {
+            return RedirectError::Io(error);
+        }
+    }
+
+    impl From<RedirectError> for io::Error {
+        fn from(error: RedirectError) -> io::Error {
+            if let RedirectError::Io(source) = error {
+                return source;
+            }
+            return io::Error::new(error.io_error().kind(), error);
+        }
+    }
+}
+
+pub use error::*;
+
+#[cfg(unix)]
 mod platform
 {
     use super::*;
@@ -107,6 +264,36 @@ mod platform
     pub trait Descriptable: AsRawFd {}
     impl<T: AsRawFd> Descriptable for T {}
 
+    /// Redirects any descriptor-backed source to any descriptor-backed destination.
+    ///
+    /// Sources include `std::io::Stdin`, whose descriptor 0 is overwritten so that subsequent reads
+    /// come from the destination.
+    ///
+    /// This also covers `OwnedFd` and `BorrowedFd<'_>` as destinations. The numeric descriptor of
+    /// the destination is used as `dst` in `dup2(dst, src)`, and ownership of it is not transferred.
+    /// Dropping an `OwnedFd` destination afterwards closes only its own descriptor; the source keeps
+    /// referring to the same open file.
+    ///
+    /// `tokio::fs::File` is covered as well, since it implements `AsRawFd` by delegating to the
+    /// inner `std::fs::File`. The redirection is a plain blocking `dup2` that does not need an async
+    /// runtime; use `AsyncRedirectable` from within async code instead.
+    ///
+    /// Sockets are descriptors as well, so `std::os::unix::net::UnixStream` works as a destination,
+    /// for example to send output to a log aggregator over IPC. The same goes for
+    /// `std::net::TcpStream`, in which case everything written after the redirection goes directly
+    /// into the TCP connection, without buffering on the way.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use io_redirect::Redirectable;
+    /// use std::os::fd::{AsFd, OwnedFd};
+    ///
+    /// let mut source = std::fs::File::create("src.txt").unwrap();
+    /// let owned: OwnedFd = std::fs::File::create("dst.txt").unwrap().into();
+    /// source.redirect(&owned.as_fd()).unwrap();
+    /// source.redirect(&owned).unwrap();
+    /// drop(owned); // `source` still writes to dst.txt
+    /// ```
     impl<T1: Descriptable, T2: Descriptable> Redirectable<T2> for T1 {
         fn redirect(&mut self, destination: &T2) -> io::Result<()> {
             let src_fd = self.as_raw_fd();
@@ -114,9 +301,17 @@ mod platform
             return libc_common::redirect_fd_to_fd(src_fd, dst_fd);
         }
     }
+
+    impl<T1: Descriptable, T2: Descriptable> FallibleRedirectable<T2> for T1 {
+        fn try_redirect(&mut self, destination: &T2) -> Result<(), RedirectError> {
+            let src_fd = self.as_raw_fd();
+            let dst_fd = destination.as_raw_fd();
+            return libc_common::redirect_fd_to_fd(src_fd, dst_fd).map_err(|source| RedirectError::DupFailed { src_fd, dst_fd, source });
+        }
+    }
 }
 
-#[cfg(any(target_os = "windows"))]
+#[cfg(windows)]
 mod platform
 {
     use super::*;
@@ -174,21 +369,161 @@ mod platform
     mod windows_sys_backend
     {
         use super::*;
+        #[cfg(feature = "std")]
+        use std::io::{Stderr, Stdin, Stdout};
         use windows_sys::Win32::Foundation::HANDLE;
-        use windows_sys::Win32::System::Console::{SetStdHandle, STD_ERROR_HANDLE, STD_HANDLE, STD_OUTPUT_HANDLE};
+        #[cfg(feature = "std")]
+        use windows_sys::Win32::System::Console::{SetStdHandle, STD_ERROR_HANDLE, STD_HANDLE, STD_INPUT_HANDLE, STD_OUTPUT_HANDLE};
+
+        /// Replaces the handle of the `File` with a duplicate of the destination handle.
+        ///
+        /// This only affects the handle owned by this `File`. Other copies of the original handle,
+        /// including ones held by the C runtime or other processes, keep referring to the original file.
+        /// When `libc_on_windows` is enabled, its implementation is used instead.
+        #[cfg(not(feature = "libc_on_windows"))]
+        impl<T: Descriptable> Redirectable<T> for File {
+            fn redirect(&mut self, destination: &T) -> io::Result<()> {
+                use std::os::windows::io::FromRawHandle;
+
+                let new_handle = duplicate_handle(destination)?;
+                *self = unsafe { File::from_raw_handle(new_handle as _) };
+                return Ok(());
+            }
+        }
+
+        /// Replaces the owned handle with a duplicate of the destination handle.
+        ///
+        /// This covers handles of any kind that are not wrapped in a `File`, such as pipes or
+        /// sockets converted into an `OwnedHandle`. As with `File`, only this handle is affected.
+        impl<T: Descriptable> Redirectable<T> for std::os::windows::io::OwnedHandle {
+            fn redirect(&mut self, destination: &T) -> io::Result<()> {
+                *self = clone_descriptor(destination)?;
+                return Ok(());
+            }
+        }
+
+        /// Duplicates a handle without redirecting anything.
+        ///
+        /// The returned handle is distinct from the one of `fd` but refers to the same object, and
+        /// is closed when the `OwnedHandle` is dropped. It is never inherited by child processes.
+        ///
+        /// # Parameters
+        /// - `fd`: The handle to duplicate.
+        ///
+        /// # Returns
+        /// - `io::Result<OwnedHandle>`: The duplicate if successful, `Err` otherwise.
+        pub fn clone_descriptor<T: Descriptable>(fd: &T) -> io::Result<std::os::windows::io::OwnedHandle> {
+            use std::os::windows::io::{FromRawHandle, OwnedHandle};
+
+            let new_handle = duplicate_handle(fd)?;
+            return Ok(unsafe { OwnedHandle::from_raw_handle(new_handle as _) });
+        }
+
+        fn duplicate_handle<T: Descriptable>(source: &T) -> io::Result<HANDLE> {
+            use windows_sys::Win32::Foundation::{DuplicateHandle, DUPLICATE_SAME_ACCESS, FALSE};
+            use windows_sys::Win32::System::Threading::GetCurrentProcess;
+
+            let mut new_handle: HANDLE = std::ptr::null_mut();
+            let result = unsafe {
+                let process = GetCurrentProcess();
+                DuplicateHandle(process, source.as_raw_handle() as HANDLE, process, &mut new_handle, 0, FALSE, DUPLICATE_SAME_ACCESS)
+            };
+            if result == 0 {
+                return Err(io::Error::last_os_error());
+            }
+            return Ok(new_handle);
+        }
+
+        /// Creates an anonymous pipe, returned as `(read_end, write_end)`.
+        ///
+        /// Both ends are inherited by child processes. Use [`pipe_cloexec`] unless that is the intent.
+        pub fn pipe() -> io::Result<(std::os::windows::io::OwnedHandle, std::os::windows::io::OwnedHandle)> {
+            return create_pipe(true);
+        }
+
+        /// Creates an anonymous pipe whose ends are not inherited by child processes, returned as
+        /// `(read_end, write_end)`.
+        pub fn pipe_cloexec() -> io::Result<(std::os::windows::io::OwnedHandle, std::os::windows::io::OwnedHandle)> {
+            return create_pipe(false);
+        }
+
+        fn create_pipe(inheritable: bool) -> io::Result<(std::os::windows::io::OwnedHandle, std::os::windows::io::OwnedHandle)> {
+            use std::os::windows::io::{FromRawHandle, OwnedHandle};
+            use windows_sys::Win32::Security::SECURITY_ATTRIBUTES;
+            use windows_sys::Win32::System::Pipes::CreatePipe;
+
+            let attributes = SECURITY_ATTRIBUTES {
+                nLength: std::mem::size_of::<SECURITY_ATTRIBUTES>() as u32,
+                lpSecurityDescriptor: std::ptr::null_mut(),
+                bInheritHandle: inheritable as i32,
+            };
+            let mut read_end: HANDLE = std::ptr::null_mut();
+            let mut write_end: HANDLE = std::ptr::null_mut();
+            if unsafe { CreatePipe(&mut read_end, &mut write_end, &attributes, 0) } == 0 {
+                return Err(io::Error::last_os_error());
+            }
+            return Ok(unsafe { (OwnedHandle::from_raw_handle(read_end as _), OwnedHandle::from_raw_handle(write_end as _)) });
+        }
+
+        /// Replaces the standard input handle of the process with the destination handle.
+        ///
+        /// `std::io::stdin()` looks up the standard input handle on each read, so reads issued after
+        /// the redirection come from the destination. Data already buffered by `Stdin` is read first.
+        #[cfg(feature = "std")]
+        impl<T: Descriptable> Redirectable<T> for Stdin {
+            fn redirect(&mut self, destination: &T) -> io::Result<()> {
+                redirect_using_setstdhandle(STD_INPUT_HANDLE, destination)
+            }
+        }
+
+        /// Checks whether `handle` currently refers to the file at `expected_path`, e.g. after redirecting it there.
+        ///
+        /// The path of the open file is obtained with `GetFinalPathNameByHandleW` and compared with
+        /// the canonical form of `expected_path`.
+        ///
+        /// # Returns
+        /// - `io::Result<bool>`: Whether `handle` refers to `expected_path`. `Ok(false)` for pipes,
+        ///   consoles and other handles that are not disk files. `Err` if the path lookup fails or
+        ///   `expected_path` cannot be canonicalized, e.g. because it does not exist.
+        pub fn is_redirect_active<T: Descriptable>(handle: &T, expected_path: &std::path::Path) -> io::Result<bool> {
+            use std::os::windows::ffi::OsStringExt;
+            use windows_sys::Win32::Storage::FileSystem::{GetFileType, GetFinalPathNameByHandleW, FILE_NAME_NORMALIZED, FILE_TYPE_DISK, VOLUME_NAME_DOS};
 
+            let handle = handle.as_raw_handle() as HANDLE;
+            if unsafe { GetFileType(handle) } != FILE_TYPE_DISK {
+                return Ok(false);
+            }
+            let mut buf = vec![0u16; 512];
+            loop {
+                let len = unsafe { GetFinalPathNameByHandleW(handle, buf.as_mut_ptr(), buf.len() as u32, FILE_NAME_NORMALIZED | VOLUME_NAME_DOS) } as usize;
+                if len == 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                if len < buf.len() {
+                    buf.truncate(len);
+                    break;
+                }
+                buf.resize(len, 0);
+            }
+            let path = std::path::PathBuf::from(std::ffi::OsString::from_wide(&buf));
+            return Ok(path == expected_path.canonicalize()?);
+        }
+
+        #[cfg(feature = "std")]
         impl<T: Descriptable> Redirectable<T> for Stdout {
             fn redirect(&mut self, destination: &T) -> io::Result<()> {
                 redirect_using_setstdhandle(STD_OUTPUT_HANDLE, destination)
             }
         }
 
+        #[cfg(feature = "std")]
         impl<T: Descriptable> Redirectable<T> for Stderr {
             fn redirect(&mut self, destination: &T) -> io::Result<()> {
                 redirect_using_setstdhandle(STD_ERROR_HANDLE, destination)
             }
         }
 
+        #[cfg(feature = "std")]
         fn redirect_using_setstdhandle<T: Descriptable>(std_handle: STD_HANDLE, destination: &T) -> io::Result<()> {
             let dst_handle = destination.as_raw_handle() as HANDLE;
             let result = unsafe { SetStdHandle(std_handle, dst_handle) };
@@ -201,175 +536,6379 @@ mod platform
 
     #[cfg(feature = "windows-sys")]
     pub use windows_sys_backend::*;
+
+    /// Handles have no descriptor numbers, so failures are reported as `RedirectError::Io`.
+    impl<S: Redirectable<T>, T: Descriptable> FallibleRedirectable<T> for S {
+        fn try_redirect(&mut self, destination: &T) -> Result<(), RedirectError> {
+            return Ok(self.redirect(destination)?);
+        }
+    }
 }
 
-#[cfg(any(all(unix, feature = "libc_on_unix"), all(target_os = "windows", feature = "libc_on_windows")))]
+#[cfg(any(all(unix, feature = "libc_on_unix"), all(windows, feature = "libc_on_windows")))]
 mod libc_common
 {
     use super::*;
     use crate::platform::Descriptor;
     use libc::dup2;
 
+    /// Makes `src` refer to the file of `dst`, rejecting `src == dst` as it hints at a mix-up.
+    ///
+    /// `dup2` is retried if it is interrupted by a signal.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all, fields(src_fd = src, dst_fd = dst)))]
     pub fn redirect_fd_to_fd(src: Descriptor, dst: Descriptor) -> io::Result<()> {
-        let result = unsafe {
-            dup2(dst, src)
-            // After this call on Windows, get_osfhandle seems to return a different value
-            // than the one passed to open_osfhandle. This is why the libc backend is off on Windows.
-        };
-        if result < 0 {
-            return Err(io::Error::last_os_error());
+        if src == dst {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, "redirect source and destination are the same fd"));
+        }
+        #[cfg(feature = "log")]
+        log::trace!("redirect fd {} → fd {}", src, dst);
+        loop {
+            let result = unsafe {
+                dup2(dst, src)
+                // After this call on Windows, get_osfhandle seems to return a different value
+                // than the one passed to open_osfhandle. This is why the libc backend is off on Windows.
+            };
+            if result >= 0 {
+                break;
+            }
+            let error = io::Error::last_os_error();
+            if error.kind() == io::ErrorKind::Interrupted {
+                #[cfg(feature = "tracing")]
+                tracing::warn!("dup2 interrupted, retrying");
+                continue;
+            }
+            #[cfg(feature = "log")]
+            log::warn!("redirect fd {} → fd {} failed: {}", src, dst, error);
+            return Err(error);
         }
 
+        #[cfg(feature = "tracing")]
+        tracing::trace!("redirected");
         return Ok(());
     }
 }
 
-#[cfg(any(all(unix, feature = "libc_on_unix"), all(target_os = "windows", feature = "libc_on_windows")))]
-mod libc_convenience
+mod options
 {
+    #[cfg(any(feature = "std", all(unix, feature = "libc_on_unix")))]
     use super::*;
+    #[cfg(any(feature = "std", all(unix, feature = "libc_on_unix")))]
     use std::fs::OpenOptions;
+    #[cfg(any(feature = "std", all(unix, feature = "libc_on_unix")))]
     use std::path::Path;
 
+    /// Options controlling how the destination of a path-based redirection is opened.
+    ///
+    /// `RedirectOptions::default()` matches `Redirectable<Path>`: the file is created if it does not
+    /// exist and written in append mode. The `include_*` fields only apply to
+    /// [`redirect_std_to_path_with_options`], which redirects stdout and stderr by default.
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct RedirectOptions
+    {
+        /// Writes to the end of the file. Cannot be combined with `truncate`.
+        pub append: bool,
+        /// Truncates the file to zero length when opening it.
+        pub truncate: bool,
+        /// Creates the file if it does not exist.
+        pub create: bool,
+        /// Creates the file and fails with `ErrorKind::AlreadyExists` if it exists already.
+        pub create_new: bool,
+        /// Redirects stdin to the file, which is then also opened for reading. If neither stdout
+        /// nor stderr is included, the file is opened read-only and must exist.
+        pub include_stdin: bool,
+        /// Redirects stdout to the file.
+        pub include_stdout: bool,
+        /// Redirects stderr to the file.
+        pub include_stderr: bool,
+        /// Permission bits of a newly created file on Unix, subject to the umask. Ignored elsewhere.
+        pub unix_mode: Option<u32>,
+        /// Keeps close-on-exec set on the opened file while it is open on Unix, so that child
+        /// processes spawned concurrently do not inherit it. The redirected sources never inherit
+        /// the flag. Ignored elsewhere.
+        pub cloexec_intermediate: bool,
+        /// Creates missing parent directories of the file before opening it.
+        pub create_parents: bool,
+    }
 
-    impl<T: Redirectable<File>> Redirectable<Path> for T {
-        fn redirect(&mut self, destination: &Path) -> io::Result<()> {
-            let dst = OpenOptions::new().read(false).write(true).create(true).append(true).open(destination)?;
-            let result = self.redirect(&dst);
-            if result.is_ok() {
-                std::mem::forget(dst);
-            }
-            return result;
+    impl Default for RedirectOptions {
+        fn default() -> RedirectOptions {
+            return RedirectOptions {
+                append: true,
+                truncate: false,
+                create: true,
+                create_new: false,
+                include_stdin: false,
+                include_stdout: true,
+                include_stderr: true,
+                unix_mode: None,
+                cloexec_intermediate: true,
+                create_parents: false,
+            };
         }
     }
-}
 
-#[cfg(any(all(unix, feature = "libc_on_unix"), all(target_os = "windows", feature = "libc_on_windows")))]
-pub use libc_convenience::*;
+    impl RedirectOptions {
+        #[cfg(any(feature = "std", all(unix, feature = "libc_on_unix")))]
+        #[cfg_attr(feature = "tracing", tracing::instrument(name = "open_destination", level = "trace", skip(self)))]
+        pub(crate) fn open(&self, path: &Path) -> io::Result<File> {
+            #[cfg(feature = "log")]
+            log::trace!("opening {:?} for redirect", path);
+            self.create_parents_of(path)?;
+            let mut options = OpenOptions::new();
+            if self.is_read_only() {
+                options.read(true);
+            } else {
+                options.read(self.include_stdin).write(true).append(self.append).truncate(self.truncate).create(self.create).create_new(self.create_new);
+            }
+            #[cfg(unix)]
+            if let Some(mode) = self.unix_mode {
+                use std::os::unix::fs::OpenOptionsExt;
+                options.mode(mode);
+            }
+            #[cfg(all(unix, feature = "libc_on_unix"))]
+            if self.cloexec_intermediate {
+                use std::os::unix::fs::OpenOptionsExt;
+                options.custom_flags(libc::O_CLOEXEC);
+            }
+            let file = options.open(path).inspect_err(|_error| {
+                #[cfg(feature = "log")]
+                log::warn!("opening {:?} for redirect failed: {}", path, _error);
+            })?;
+            #[cfg(all(unix, feature = "libc_on_unix"))]
+            if !self.cloexec_intermediate {
+                use std::os::fd::AsRawFd;
+                if unsafe { libc::fcntl(file.as_raw_fd(), libc::F_SETFD, 0) } < 0 {
+                    return Err(io::Error::last_os_error());
+                }
+            }
+            #[cfg(feature = "tracing")]
+            tracing::trace!("opened");
+            return Ok(file);
+        }
 
-mod convenience
-{
-    use super::*;
-    use std::fs::OpenOptions;
-    use std::io::{stderr, stdout};
-    use std::path::Path;
-    pub fn redirect_std_to_path(destination: &Path, append: bool) -> io::Result<()> {
-        let dst = OpenOptions::new().read(false).write(true).create(true).append(append).open(destination)?;
-        stdout().redirect(&dst)?;
-        stderr().redirect(&dst)?;
-        std::mem::forget(dst);
-        return Ok(());
+        /// Creates the parent directories of `path` if `create_parents` is set.
+        #[cfg(any(feature = "std", all(unix, feature = "libc_on_unix")))]
+        pub(crate) fn create_parents_of(&self, path: &Path) -> io::Result<()> {
+            if self.create_parents {
+                std::fs::create_dir_all(path.parent().unwrap_or(Path::new(".")))?;
+            }
+            return Ok(());
+        }
+
+        /// Returns whether only stdin is selected, in which case the file is opened read-only and
+        /// `append`, `truncate`, `create` and `create_new` are ignored.
+        #[cfg(any(feature = "std", all(unix, feature = "libc_on_unix")))]
+        fn is_read_only(&self) -> bool {
+            return self.include_stdin && !self.include_stdout && !self.include_stderr;
+        }
+
+        /// Returns the `open(2)` flags matching these options, like [`RedirectOptions::open`].
+        #[cfg(all(unix, feature = "libc_on_unix"))]
+        pub(crate) fn open_flags(&self) -> libc::c_int {
+            if self.is_read_only() {
+                return libc::O_RDONLY | if self.cloexec_intermediate { libc::O_CLOEXEC } else { 0 };
+            }
+            let mut flags = if self.include_stdin { libc::O_RDWR } else { libc::O_WRONLY };
+            if self.append {
+                flags |= libc::O_APPEND;
+            }
+            if self.truncate {
+                flags |= libc::O_TRUNC;
+            }
+            if self.create_new {
+                flags |= libc::O_CREAT | libc::O_EXCL;
+            } else if self.create {
+                flags |= libc::O_CREAT;
+            }
+            if self.cloexec_intermediate {
+                flags |= libc::O_CLOEXEC;
+            }
+            return flags;
+        }
     }
 }
 
-pub use convenience::*;
-pub use platform::*;
+pub use options::*;
 
-#[cfg(test)]
-mod tests {
+#[cfg(feature = "std")]
+mod command
+{
     use super::*;
-    use std::io::{Read, Write};
-    use std::mem::ManuallyDrop;
-    use libc::close;
+    use std::path::Path;
+    use std::process::{Command, Stdio};
 
-    #[cfg(any(all(unix, feature = "libc_on_unix"), all(target_os = "windows", feature = "libc_on_windows")))]
-    #[test]
-    fn redirects_file_to_file() {
-        // Arrange
-        let tempdir = tempfile::tempdir().unwrap();
-        let mut file1 = File::create(tempdir.path().join("file1.txt")).unwrap();
-        let mut file2 = File::create(tempdir.path().join("file2.txt")).unwrap();
+    /// Extension methods for setting up the standard streams of a child process from paths.
+    ///
+    /// Output files are opened like `RedirectOptions::default()`: in append mode and created if
+    /// they do not exist.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use io_redirect::CommandRedirectExt;
+    /// use std::process::Command;
+    ///
+    /// let status = Command::new("make")
+    ///     .redirect_stdout_stderr_to_same_path("build.log").unwrap()
+    ///     .status()
+    ///     .unwrap();
+    /// ```
+    pub trait CommandRedirectExt
+    {
+        /// Sends the stdout of the child process to the file at `path`.
+        fn redirect_stdout_to_path<P: AsRef<Path>>(&mut self, path: P) -> io::Result<&mut Command>;
 
-        // Act
-        file1.redirect(&file2).unwrap();
-        file1.write_all(b"Hello,").unwrap();
-        file1.flush().unwrap();
-        file2.write_all(b" World!").unwrap();
-        file2.flush().unwrap();
+        /// Sends the stderr of the child process to the file at `path`.
+        fn redirect_stderr_to_path<P: AsRef<Path>>(&mut self, path: P) -> io::Result<&mut Command>;
 
-        // Assert
-        let mut dst_file = File::open(tempdir.path().join("file2.txt")).unwrap();
-        let mut dst_contents = String::new();
-        dst_file.read_to_string(&mut dst_contents).unwrap();
-        assert_eq!(dst_contents, "Hello, World!");
+        /// Makes the child process read its stdin from the file at `path`.
+        fn redirect_stdin_from_path<P: AsRef<Path>>(&mut self, path: P) -> io::Result<&mut Command>;
 
-        let mut old_file1_contents = String::new();
-        let mut old_file1 = File::open(tempdir.path().join("file1.txt")).unwrap();
-        old_file1.read_to_string(&mut old_file1_contents).unwrap();
-        assert_eq!(old_file1_contents, "");
+        /// Sends both stdout and stderr of the child process to the file at `path`.
+        fn redirect_stdout_stderr_to_same_path<P: AsRef<Path>>(&mut self, path: P) -> io::Result<&mut Command>;
     }
 
-    #[cfg(any(all(unix, feature = "libc_on_unix"), all(target_os = "windows", feature = "libc_on_windows")))]
-    #[test]
-    fn redirects_file_to_path() {
-        // Arrange
-        let tempdir = tempfile::tempdir().unwrap();
-        let src_path = tempdir.path().join("src.txt");
-        let dst_path = tempdir.path().join("dst.txt");
-        let mut src = OpenOptions::new().create(true).read(true).write(true).open(&src_path).unwrap();
+    impl CommandRedirectExt for Command {
+        fn redirect_stdout_to_path<P: AsRef<Path>>(&mut self, path: P) -> io::Result<&mut Command> {
+            let file = RedirectOptions::default().open(path.as_ref())?;
+            return Ok(self.stdout(Stdio::from(file)));
+        }
 
-        // Act
-        src.redirect(dst_path.as_path()).unwrap();
-        src.write_all(b"abc").unwrap();
-        src.flush().unwrap();
+        fn redirect_stderr_to_path<P: AsRef<Path>>(&mut self, path: P) -> io::Result<&mut Command> {
+            let file = RedirectOptions::default().open(path.as_ref())?;
+            return Ok(self.stderr(Stdio::from(file)));
+        }
 
-        // Assert
-        let mut dst_contents = String::new();
-        File::open(&dst_path).unwrap().read_to_string(&mut dst_contents).unwrap();
-        assert_eq!(dst_contents, "abc");
+        fn redirect_stdin_from_path<P: AsRef<Path>>(&mut self, path: P) -> io::Result<&mut Command> {
+            let file = File::open(path)?;
+            return Ok(self.stdin(Stdio::from(file)));
+        }
 
-        let mut original_contents = String::new();
-        File::open(&src_path).unwrap().read_to_string(&mut original_contents).unwrap();
-        assert_eq!(original_contents, "");
+        fn redirect_stdout_stderr_to_same_path<P: AsRef<Path>>(&mut self, path: P) -> io::Result<&mut Command> {
+            let file = RedirectOptions::default().open(path.as_ref())?;
+            let clone = file.try_clone()?;
+            return Ok(self.stdout(Stdio::from(file)).stderr(Stdio::from(clone)));
+        }
     }
+}
 
-    #[cfg(any(all(unix, feature = "libc_on_unix"), all(target_os = "windows", feature = "libc_on_windows")))]
-    #[test]
-    fn errors_on_redirect_to_directory() {
-        // Arrange
-        let tempdir = tempfile::tempdir().unwrap();
-        let dir_path = tempdir.path();
-        let mut src = File::create(dir_path.join("somefile.txt")).unwrap();
+#[cfg(feature = "std")]
+pub use command::*;
 
-        // Act
-        let err = src.redirect(dir_path).unwrap_err();
+#[cfg(all(feature = "std", any(all(unix, feature = "libc_on_unix"), all(windows, feature = "libc_on_windows"))))]
+mod libc_convenience
+{
+    use super::*;
+    use std::ffi::OsStr;
+    use std::path::Path;
 
-        // Assert
-        assert!(err.raw_os_error().is_some());
+    /// Keeps the destination opened by [`redirect_to_path_owning`] alive.
+    ///
+    /// Dropping the handle closes the destination. On Unix the redirected source holds its own
+    /// reference to the file, so this never affects it. On Windows a redirected standard stream may
+    /// refer to the very handle kept here, so the handle must outlive the redirection.
+    #[derive(Debug)]
+    pub struct PathRedirectHandle
+    {
+        destination: File,
     }
 
-    #[cfg(any(all(unix, feature = "libc_on_unix"), all(target_os = "windows", feature = "libc_on_windows")))]
-    #[test]
-    fn errors_on_redirect_with_missing_parent_directory() {
-        // Arrange
-        let tempdir = tempfile::tempdir().unwrap();
-        let mut src = File::create(tempdir.path().join("s.txt")).unwrap();
-        let bad_path = tempdir.path().join("no_such_dir").join("f.txt");
-
-        // Act
-        let err = src.redirect(bad_path.as_path()).unwrap_err();
+    impl PathRedirectHandle {
+        /// Closes the destination opened for the redirection.
+        pub fn close(self) {
+            drop(self.destination);
+        }
+    }
 
-        // Assert
-        assert!(err.raw_os_error().is_some());
+    /// Redirects I/O to the file at `destination` and returns the handle of the opened file.
+    ///
+    /// The file is opened in append mode and created if it does not exist. Unlike
+    /// `Redirectable<Path>`, the caller decides when the opened file is closed.
+    ///
+    /// # Parameters
+    /// - `source`: The entity to redirect.
+    /// - `destination`: The path of the file to redirect to.
+    ///
+    /// # Returns
+    /// - `io::Result<PathRedirectHandle>`: The handle of the opened file if successful, `Err` otherwise.
+    pub fn redirect_to_path_owning<T: Redirectable<File>, P: AsRef<Path>>(source: &mut T, destination: P) -> io::Result<PathRedirectHandle> {
+        let dst = RedirectOptions::default().open(destination.as_ref())?;
+        source.redirect(&dst)?;
+        return Ok(PathRedirectHandle { destination: dst });
     }
 
-    #[cfg(any(all(unix, feature = "libc_on_unix")))]
-    #[test]
-    fn errors_on_redirect_to_closed_fd() {
-        use std::os::fd::AsRawFd;
+    /// Redirects I/O to the file at `path`, opened according to `opts`.
+    ///
+    /// # Parameters
+    /// - `source`: The entity to redirect.
+    /// - `path`: The path of the file to redirect to.
+    /// - `opts`: How to open the file.
+    ///
+    /// # Returns
+    /// - `io::Result<()>`: `Ok` if successful, `Err` otherwise.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use io_redirect::{redirect_to_path_with_options, RedirectOptions};
+    /// use std::path::Path;
+    ///
+    /// let opts = RedirectOptions { append: false, truncate: true, unix_mode: Some(0o600), ..Default::default() };
+    /// redirect_to_path_with_options(&mut std::io::stdout(), Path::new("out.log"), &opts).unwrap();
+    /// ```
+    pub fn redirect_to_path_with_options<T: Redirectable<File>, P: AsRef<Path>>(source: &mut T, path: P, opts: &RedirectOptions) -> io::Result<()> {
+        let dst = opts.open(path.as_ref())?;
+        source.redirect(&dst)?;
+        if cfg!(not(unix)) {
+            std::mem::forget(dst);
+        }
+        return Ok(());
+    }
+
+    /// Redirects I/O to the file at `path`, created with the permission bits `mode` if it does not exist.
+    ///
+    /// This is [`redirect_to_path_with_options`] with `opts.unix_mode` replaced by `mode`. On Unix,
+    /// `mode` is passed to `open(2)`, so there is no window in which the file has wider
+    /// permissions. On Windows, `mode` is ignored.
+    ///
+    /// # Parameters
+    /// - `source`: The entity to redirect.
+    /// - `path`: The path of the file to redirect to.
+    /// - `opts`: How to open the file.
+    /// - `mode`: The permission bits of a newly created file, such as `0o600`.
+    ///
+    /// # Returns
+    /// - `io::Result<()>`: `Ok` if successful, `Err` otherwise.
+    ///
+    /// # Notes
+    /// The process umask still clears bits from `mode`, as with any `open(2)` call. An existing
+    /// file keeps its permissions.
+    pub fn redirect_to_path_with_mode<T: Redirectable<File>, P: AsRef<Path>>(source: &mut T, path: P, opts: &RedirectOptions, mode: u32) -> io::Result<()> {
+        return redirect_to_path_with_options(source, path, &RedirectOptions { unix_mode: Some(mode), ..opts.clone() });
+    }
+
+    /// Redirects I/O to the file at `path` like [`redirect_to_path_with_options`], creating missing
+    /// parent directories first regardless of `opts.create_parents`.
+    ///
+    /// # Parameters
+    /// - `source`: The entity to redirect.
+    /// - `path`: The path of the file to redirect to.
+    /// - `opts`: How to open the file.
+    ///
+    /// # Returns
+    /// - `io::Result<()>`: `Ok` if successful, `Err` otherwise. Directories created before a
+    ///   failure are not removed.
+    pub fn redirect_to_path_create_parents<T: Redirectable<File>, P: AsRef<Path>>(source: &mut T, path: P, opts: &RedirectOptions) -> io::Result<()> {
+        return redirect_to_path_with_options(source, path, &RedirectOptions { create_parents: true, ..opts.clone() });
+    }
+
+    /// Redirects I/O to the file at `destination` like `Redirectable<Path>`, accepting any path-like value.
+    ///
+    /// `Redirectable` only accepts the unsized `Path`, `str` and `OsStr` as path destinations, so
+    /// owned values such as `String`, `PathBuf` or `OsString` need a conversion like `as_path()`
+    /// first. This function takes them directly.
+    ///
+    /// # Parameters
+    /// - `source`: The entity to redirect.
+    /// - `destination`: The path of the file to redirect to.
+    ///
+    /// # Returns
+    /// - `io::Result<()>`: `Ok` if successful, `Err` otherwise.
+    ///
+    /// # Notes
+    /// A blanket `impl<P: AsRef<Path>> Redirectable<P>` is not possible, as it would overlap with the
+    /// implementations for descriptor-backed destinations on Unix-like platforms.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use io_redirect::redirect_to_path_like;
+    ///
+    /// let log_path = format!("{}.log", env!("CARGO_PKG_NAME"));
+    /// redirect_to_path_like(&mut std::io::stdout(), &log_path).unwrap();
+    /// ```
+    pub fn redirect_to_path_like<T: Redirectable<Path>, P: AsRef<Path>>(source: &mut T, destination: P) -> io::Result<()> {
+        return source.redirect(destination.as_ref());
+    }
+
+    /// Opens the destination in append mode, created if it does not exist, using the default
+    /// [`RedirectOptions`]. On Unix the file is opened with `O_CLOEXEC`, so a child process spawned
+    /// by another thread before the file is closed again does not inherit it.
+    impl<T: Redirectable<File>> Redirectable<Path> for T {
+        fn redirect(&mut self, destination: &Path) -> io::Result<()> {
+            return redirect_to_path_with_options(self, destination, &RedirectOptions::default());
+        }
+    }
+
+    impl<T: FallibleRedirectable<File>> FallibleRedirectable<Path> for T {
+        fn try_redirect(&mut self, destination: &Path) -> Result<(), RedirectError> {
+            let dst = RedirectOptions::default().open(destination).map_err(|source| RedirectError::OpenDestinationFailed { path: destination.to_path_buf(), source })?;
+            self.try_redirect(&dst)?;
+            if cfg!(not(unix)) {
+                std::mem::forget(dst);
+            }
+            return Ok(());
+        }
+    }
+
+    /// Redirects I/O to the file at the path given as a string slice.
+    ///
+    /// `PathBuf`, `String` and `OsString` cannot have such an implementation as it would overlap
+    /// with the descriptor-based one. Pass them with a deref instead, e.g.
+    /// `source.redirect(&*path_buf)` or `source.redirect(string.as_str())`.
+    impl<T: Redirectable<Path>> Redirectable<str> for T {
+        fn redirect(&mut self, destination: &str) -> io::Result<()> {
+            return self.redirect(Path::new(destination));
+        }
+    }
+
+    /// Redirects I/O to the file at the path given as an OS string slice.
+    ///
+    /// This fits paths taken from environment variables or command-line arguments, including ones
+    /// that are not valid UTF-8. Pass an `OsString` with a deref, e.g. `source.redirect(&*os_string)`.
+    impl<T: Redirectable<Path>> Redirectable<OsStr> for T {
+        fn redirect(&mut self, destination: &OsStr) -> io::Result<()> {
+            return self.redirect(Path::new(destination));
+        }
+    }
+}
+
+#[cfg(all(feature = "std", any(all(unix, feature = "libc_on_unix"), all(windows, feature = "libc_on_windows"))))]
+pub use libc_convenience::*;
+
+#[cfg(any(all(unix, feature = "libc_on_unix"), all(windows, feature = "libc_on_windows", feature = "raw-fd")))]
+mod raw
+{
+    use super::*;
+    #[cfg(unix)]
+    use std::os::fd::{AsRawFd, RawFd};
+
+    /// Redirects the raw descriptor `src` so that it refers to the same file as `dst`.
+    ///
+    /// This is meant for descriptors obtained from C libraries or syscalls such as `openat` that
+    /// are not wrapped in a Rust type. On Windows the descriptors are C runtime descriptors.
+    ///
+    /// # Safety
+    /// - `src` and `dst` must be valid open descriptors.
+    /// - `src` must differ from `dst`.
+    /// - `src` is silently closed and reused, so whoever owns it must tolerate that.
+    #[cfg(feature = "raw-fd")]
+    pub unsafe fn redirect_raw_fd(src: Descriptor, dst: Descriptor) -> io::Result<()> {
+        return libc_common::redirect_fd_to_fd(src, dst);
+    }
+
+    /// Redirects the descriptor of `src` so that it refers to the same file as the one of `dst`.
+    ///
+    /// This is the non-generic-trait counterpart of `Redirectable` for callers that already hold
+    /// typed descriptor wrappers such as `OwnedFd` or `BorrowedFd`.
+    #[cfg(unix)]
+    pub fn redirect_descriptor<S: Descriptable, D: Descriptable>(src: &S, dst: &D) -> io::Result<()> {
+        return libc_common::redirect_fd_to_fd(src.as_raw_fd(), dst.as_raw_fd());
+    }
+
+    /// Flushes `source` and then redirects its underlying descriptor to the one of `destination`.
+    ///
+    /// Bytes still sitting in the buffer are written to the original file first, and only what is
+    /// written afterwards goes to `destination`. If flushing fails nothing is redirected.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use io_redirect::redirect_buffered;
+    /// use std::io::{BufWriter, Write};
+    ///
+    /// let mut writer = BufWriter::new(std::fs::File::create("first.txt").unwrap());
+    /// write!(writer, "to first").unwrap();
+    /// redirect_buffered(&mut writer, &std::fs::File::create("second.txt").unwrap()).unwrap();
+    /// write!(writer, "to second").unwrap();
+    /// ```
+    ///
+    /// # Notes
+    /// This is a function rather than an implementation of `Redirectable` for `BufWriter`, because
+    /// such an implementation would overlap with the blanket one for descriptor-backed types.
+    #[cfg(unix)]
+    pub fn redirect_buffered<W: std::io::Write + AsRawFd, D: Descriptable>(source: &mut std::io::BufWriter<W>, destination: &D) -> io::Result<()> {
+        std::io::Write::flush(source)?;
+        return libc_common::redirect_fd_to_fd(source.get_ref().as_raw_fd(), destination.as_raw_fd());
+    }
+
+    /// Redirects the raw descriptor `src` so that it refers to the same file as `dst`.
+    ///
+    /// Unlike `Redirectable`, this is not generic over the destination, so a single copy serves every
+    /// destination type. This suits code that picks destinations at runtime, e.g. from a configuration enum.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use io_redirect::redirect_to_dyn;
+    /// use std::os::fd::AsRawFd;
+    ///
+    /// let destination: Box<dyn AsRawFd> = Box::new(std::fs::File::create("out.txt").unwrap());
+    /// redirect_to_dyn(1, destination.as_ref()).unwrap();
+    /// ```
+    #[cfg(unix)]
+    pub fn redirect_to_dyn(src: RawFd, dst: &dyn AsRawFd) -> io::Result<()> {
+        return libc_common::redirect_fd_to_fd(src, dst.as_raw_fd());
+    }
+
+    /// Redirects the descriptor of `src` so that it refers to the same file as the one of `dst`.
+    ///
+    /// This is the trait object counterpart of [`redirect_descriptor`].
+    #[cfg(unix)]
+    pub fn redirect_from_dyn(src: &mut dyn AsRawFd, dst: &dyn AsRawFd) -> io::Result<()> {
+        return redirect_to_dyn(src.as_raw_fd(), dst);
+    }
+
+    /// A raw descriptor number, such as one received from C code, usable with `Redirectable`.
+    ///
+    /// `FdRef` does not own the descriptor and never closes it. Since it implements `AsRawFd`, it
+    /// can be both the source and the destination of a redirection:
+    ///
+    /// ```no_run
+    /// use io_redirect::{FdRef, Redirectable};
+    ///
+    /// let mut src = FdRef::try_from_raw_fd(1).unwrap();
+    /// let dst = FdRef::try_from_raw_fd(2).unwrap();
+    /// src.redirect(&dst).unwrap();
+    /// ```
+    #[cfg(unix)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct FdRef(RawFd);
+
+    #[cfg(unix)]
+    impl FdRef {
+        /// Wraps `fd` without checking that it is an open descriptor.
+        ///
+        /// # Safety
+        /// `fd` must be an open descriptor for as long as the `FdRef` is used.
+        pub unsafe fn from_raw_fd_unchecked(fd: RawFd) -> FdRef {
+            return FdRef(fd);
+        }
+
+        /// Wraps `fd` after checking with `fcntl(F_GETFD)` that it is an open descriptor.
+        pub fn try_from_raw_fd(fd: RawFd) -> io::Result<FdRef> {
+            if unsafe { libc::fcntl(fd, libc::F_GETFD) } < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            return Ok(FdRef(fd));
+        }
+    }
+
+    #[cfg(unix)]
+    impl AsRawFd for FdRef {
+        fn as_raw_fd(&self) -> RawFd {
+            return self.0;
+        }
+    }
+}
+
+#[cfg(any(all(unix, feature = "libc_on_unix"), all(windows, feature = "libc_on_windows", feature = "raw-fd")))]
+pub use raw::*;
+
+#[cfg(all(unix, feature = "libc_on_unix"))]
+mod dup
+{
+    use super::*;
+    use std::os::fd::{AsRawFd, FromRawFd, IntoRawFd, OwnedFd, RawFd};
+    use libc::{dup, fcntl, F_DUPFD_CLOEXEC};
+
+    /// Duplicates a descriptor without redirecting anything.
+    ///
+    /// The returned descriptor is distinct from the one of `fd` but refers to the same open file,
+    /// sharing its offset and status flags. It is closed when the `OwnedFd` is dropped.
+    ///
+    /// # Parameters
+    /// - `fd`: The descriptor to duplicate.
+    ///
+    /// # Returns
+    /// - `io::Result<OwnedFd>`: The duplicate if successful, `Err` otherwise.
+    pub fn clone_descriptor<T: AsRawFd>(fd: &T) -> io::Result<OwnedFd> {
+        let cloned = unsafe { dup(fd.as_raw_fd()) };
+        if cloned < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        return Ok(unsafe { OwnedFd::from_raw_fd(cloned) });
+    }
+
+    /// Duplicates a descriptor like [`clone_descriptor`], with close-on-exec set on the duplicate.
+    ///
+    /// The flag is set atomically through `F_DUPFD_CLOEXEC`, so the duplicate is never inherited by
+    /// child processes, even ones spawned concurrently from other threads.
+    pub fn clone_descriptor_cloexec<T: AsRawFd>(fd: &T) -> io::Result<OwnedFd> {
+        let cloned = unsafe { fcntl(fd.as_raw_fd(), F_DUPFD_CLOEXEC, 0) };
+        if cloned < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        return Ok(unsafe { OwnedFd::from_raw_fd(cloned) });
+    }
+
+    /// Duplicates the descriptor number `fd` with close-on-exec set, using `F_DUPFD_CLOEXEC`.
+    ///
+    /// This is how redirections in this crate save the original target of a descriptor, so that
+    /// the saved copy never leaks into child processes.
+    pub fn dup_cloexec(fd: RawFd) -> io::Result<OwnedFd> {
+        return clone_descriptor_cloexec(&fd);
+    }
+
+    /// An owned raw descriptor that is closed when dropped.
+    ///
+    /// This is a lighter alternative to wrapping a descriptor in `ManuallyDrop<File>` when only the
+    /// number matters. Errors from `close` are ignored on drop, so dropping a `DupFd` whose descriptor
+    /// was already closed does not panic.
+    #[derive(Debug)]
+    pub struct DupFd(RawFd);
+
+    impl DupFd {
+        /// Takes ownership of `fd`.
+        ///
+        /// # Safety
+        /// `fd` must be an open descriptor that is not owned by anything else, as it is closed when
+        /// the `DupFd` is dropped, or a negative value that is never a valid descriptor.
+        pub unsafe fn new(fd: RawFd) -> DupFd {
+            return DupFd(fd);
+        }
+    }
+
+    impl Drop for DupFd {
+        fn drop(&mut self) {
+            unsafe { libc::close(self.0) };
+        }
+    }
+
+    impl AsRawFd for DupFd {
+        fn as_raw_fd(&self) -> RawFd {
+            return self.0;
+        }
+    }
+
+    impl IntoRawFd for DupFd {
+        fn into_raw_fd(self) -> RawFd {
+            return std::mem::ManuallyDrop::new(self).0;
+        }
+    }
+
+    impl From<OwnedFd> for DupFd {
+        fn from(fd: OwnedFd) -> DupFd {
+            return DupFd(fd.into_raw_fd());
+        }
+    }
+
+    impl std::ops::Deref for DupFd {
+        type Target = RawFd;
+
+        fn deref(&self) -> &RawFd {
+            return &self.0;
+        }
+    }
+}
+
+#[cfg(all(unix, feature = "libc_on_unix"))]
+pub use dup::*;
+
+#[cfg(all(unix, feature = "libc_on_unix"))]
+mod guard
+{
+    use super::*;
+    use std::os::fd::{AsRawFd, OwnedFd, RawFd};
+
+    /// Restores redirected sources to their original destinations when dropped.
+    ///
+    /// Returned by [`RedirectableExt::save_and_redirect`] and [`redirect_std_to_path_guarded`]. The
+    /// original destinations are kept alive through close-on-exec duplicates so that child processes
+    /// do not inherit them. Errors while restoring on drop are ignored; with the `log` feature they
+    /// are reported as `warn!` records.
+    #[derive(Debug)]
+    pub struct RedirectGuard
+    {
+        saved: Vec<(RawFd, OwnedFd)>,
+    }
+
+    impl RedirectGuard {
+        /// Abandons the restore, leaving the sources redirected, and closes the saved duplicates.
+        pub fn discard(mut self) {
+            self.saved.clear();
+        }
+
+        /// Abandons the restore, leaving the sources redirected, and hands over the saved duplicate
+        /// of the original destination.
+        ///
+        /// The caller owns the returned descriptor and is responsible for closing it, e.g. by
+        /// passing it to a C library or back to [`RedirectGuard::from_saved_fd`]. If the guard covers
+        /// several sources, as with `redirect_std_to_path_guarded`, the duplicate saved for the
+        /// first one is returned and the others are closed.
+        pub fn into_saved_fd(mut self) -> RawFd {
+            use std::os::fd::IntoRawFd;
+
+            let mut saved = std::mem::take(&mut self.saved).into_iter();
+            return saved.next().map_or(-1, |(_, saved_fd)| saved_fd.into_raw_fd());
+        }
+
+        /// Creates a guard that restores `original_src_fd` to the destination of `saved_fd` when dropped.
+        ///
+        /// # Safety
+        /// - `saved_fd` must be an open descriptor that is not owned by anything else, such as one
+        ///   returned by [`RedirectGuard::into_saved_fd`]. The guard takes ownership and closes it.
+        /// - `original_src_fd` must stay open for as long as the guard exists, and must be safe to
+        ///   overwrite with `dup2` when the guard is dropped.
+        pub unsafe fn from_saved_fd(saved_fd: RawFd, original_src_fd: RawFd) -> RedirectGuard {
+            use std::os::fd::FromRawFd;
+
+            return RedirectGuard { saved: vec![(original_src_fd, unsafe { OwnedFd::from_raw_fd(saved_fd) })] };
+        }
+    }
+
+    impl RedirectGuard {
+        fn restore(&mut self) -> io::Result<()> {
+            let mut result = Ok(());
+            for (original_fd, saved) in self.saved.drain(..).rev() {
+                if let Err(e) = libc_common::redirect_fd_to_fd(original_fd, saved.as_raw_fd()) {
+                    result = result.and(Err(e));
+                }
+            }
+            return result;
+        }
+    }
+
+    impl Drop for RedirectGuard {
+        fn drop(&mut self) {
+            let _ = self.restore();
+        }
+    }
+
+    /// Redirects `stdout`, and `stderr` if `include_stderr` is set, to the file at `destination`
+    /// and returns a guard that restores them on drop.
+    ///
+    /// The file is opened in append mode and created if it does not exist. `stdout` is flushed
+    /// before being redirected so that earlier output does not end up in the file.
+    ///
+    /// # Parameters
+    /// - `destination`: The path of the file to redirect to.
+    /// - `include_stderr`: Whether to redirect `stderr` as well.
+    ///
+    /// # Returns
+    /// - `io::Result<RedirectGuard>`: A guard restoring the original destinations if successful,
+    ///   `Err` otherwise. On failure, nothing is left redirected.
+    ///
+    /// # Notes
+    /// `stdout` is line-buffered, so flush it before dropping the guard if the last line written
+    /// to the file does not end with a newline.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use io_redirect::redirect_std_to_path_guarded;
+    ///
+    /// let guard = redirect_std_to_path_guarded("captured.log", true).unwrap();
+    /// println!("goes to captured.log");
+    /// drop(guard);
+    /// println!("goes to the original stdout");
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn redirect_std_to_path_guarded<P: AsRef<std::path::Path>>(destination: P, include_stderr: bool) -> io::Result<RedirectGuard> {
+        use std::io::Write;
+
+        let file = RedirectOptions::default().open(destination.as_ref())?;
+        std::io::stdout().flush()?;
+        let mut guard = std::io::stdout().save_and_redirect(&file)?;
+        if include_stderr {
+            let mut stderr_guard = std::io::stderr().save_and_redirect(&file)?;
+            guard.saved.append(&mut stderr_guard.saved);
+        }
+        return Ok(guard);
+    }
+
+    /// Extension methods for entities that can be redirected.
+    pub trait RedirectableExt
+    {
+        /// Redirects I/O to a specified destination and returns a guard that undoes it on drop.
+        ///
+        /// # Parameters
+        /// - `dest`: A reference to the target destination.
+        ///
+        /// # Returns
+        /// - `io::Result<RedirectGuard>`: A guard restoring the original destination if successful,
+        ///   `Err` otherwise.
+        ///
+        /// # Examples
+        /// ```no_run
+        /// use io_redirect::RedirectableExt;
+        ///
+        /// let destination = std::fs::File::create("dst.txt").unwrap();
+        /// let guard = std::io::stdout().save_and_redirect(&destination).unwrap();
+        /// println!("goes to dst.txt");
+        /// drop(guard);
+        /// println!("goes to the original stdout");
+        /// ```
+        fn save_and_redirect<T: ?Sized>(&mut self, dest: &T) -> io::Result<RedirectGuard>
+        where
+            Self: Redirectable<T>;
+
+        /// Redirects I/O to a specified destination only if `condition` is true.
+        ///
+        /// This is the method form of [`redirect_conditional`].
+        ///
+        /// # Parameters
+        /// - `dest`: A reference to the target destination.
+        /// - `condition`: Whether to redirect at all.
+        ///
+        /// # Returns
+        /// - `io::Result<()>`: `Ok` if successful or if `condition` is false, `Err` otherwise.
+        fn redirect_if<T: ?Sized>(&mut self, dest: &T, condition: bool) -> io::Result<()>
+        where
+            Self: Redirectable<T>;
+    }
+
+    impl<S: Descriptable> RedirectableExt for S {
+        fn save_and_redirect<T: ?Sized>(&mut self, dest: &T) -> io::Result<RedirectGuard>
+        where
+            Self: Redirectable<T>,
+        {
+            let original_fd = self.as_raw_fd();
+            let saved = dup_cloexec(original_fd)?;
+
+            self.redirect(dest)?;
+
+            return Ok(RedirectGuard { saved: vec![(original_fd, saved)] });
+        }
+
+        fn redirect_if<T: ?Sized>(&mut self, dest: &T, condition: bool) -> io::Result<()>
+        where
+            Self: Redirectable<T>,
+        {
+            return redirect_conditional(self, dest, condition);
+        }
+    }
+
+    /// Runs `f` while `source` is redirected to `dest`, restoring the original destination afterwards.
+    ///
+    /// The original destination is restored even if `f` panics.
+    ///
+    /// # Parameters
+    /// - `source`: The entity to redirect.
+    /// - `dest`: A reference to the target destination.
+    /// - `f`: The closure to run while the redirection is in place.
+    ///
+    /// # Returns
+    /// - `io::Result<R>`: The result of `f` if the redirection and the restore succeed, `Err` otherwise.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use io_redirect::scoped_redirect;
+    ///
+    /// let destination = std::fs::File::create("dst.txt").unwrap();
+    /// scoped_redirect(&mut std::io::stdout(), &destination, || println!("goes to dst.txt")).unwrap();
+    /// println!("goes to the original stdout");
+    /// ```
+    pub fn scoped_redirect<T, U, F, R>(source: &mut T, dest: &U, f: F) -> io::Result<R>
+    where
+        T: Redirectable<U> + Descriptable,
+        U: ?Sized,
+        F: FnOnce() -> R,
+    {
+        let mut guard = source.save_and_redirect(dest)?;
+        let result = f();
+        guard.restore()?;
+        return Ok(result);
+    }
+
+    /// Nested redirections that are undone in reverse order.
+    ///
+    /// Each [`RedirectStack::push`] saves the current target of the source descriptor before
+    /// redirecting it, and each [`RedirectStack::pop`] restores the most recently saved one.
+    /// Dropping the stack pops all remaining redirections.
+    #[derive(Debug, Default)]
+    pub struct RedirectStack
+    {
+        entries: Vec<(OwnedFd, RawFd)>,
+    }
+
+    impl RedirectStack {
+        /// Creates an empty stack.
+        pub fn new() -> RedirectStack {
+            return RedirectStack::default();
+        }
+
+        /// Saves the current target of `src_fd` and redirects it to the file of `dst_fd`.
+        pub fn push(&mut self, src_fd: RawFd, dst_fd: RawFd) -> io::Result<()> {
+            let saved = dup_cloexec(src_fd)?;
+            libc_common::redirect_fd_to_fd(src_fd, dst_fd)?;
+            self.entries.push((saved, src_fd));
+            return Ok(());
+        }
+
+        /// Restores the source descriptor of the most recent redirection.
+        ///
+        /// # Returns
+        /// - `io::Result<()>`: `Ok` if successful, `Err` with `ErrorKind::InvalidInput` if the stack
+        ///   is empty, or the OS error if restoring fails. A redirection that failed to be restored
+        ///   stays on the stack.
+        pub fn pop(&mut self) -> io::Result<()> {
+            let Some((saved, src_fd)) = self.entries.pop() else {
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, "redirect stack is empty"));
+            };
+            if let Err(e) = libc_common::redirect_fd_to_fd(src_fd, saved.as_raw_fd()) {
+                self.entries.push((saved, src_fd));
+                return Err(e);
+            }
+            return Ok(());
+        }
+
+        /// Returns the number of redirections that have not been popped yet.
+        pub fn len(&self) -> usize {
+            return self.entries.len();
+        }
+
+        /// Returns `true` if there is no redirection to pop.
+        pub fn is_empty(&self) -> bool {
+            return self.entries.is_empty();
+        }
+    }
+
+    impl Drop for RedirectStack {
+        fn drop(&mut self) {
+            while let Some((saved, src_fd)) = self.entries.pop() {
+                let _ = libc_common::redirect_fd_to_fd(src_fd, saved.as_raw_fd());
+            }
+        }
+    }
+
+    /// A group of redirections that are applied together or not at all.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use io_redirect::RedirectContext;
+    /// use std::os::fd::AsRawFd;
+    ///
+    /// let log = std::fs::File::create("log.txt").unwrap();
+    /// let applied = RedirectContext::new().add(1, log.as_raw_fd()).add(2, log.as_raw_fd()).apply().unwrap();
+    /// println!("goes to log.txt");
+    /// drop(applied);
+    /// println!("goes to the original stdout");
+    /// ```
+    #[derive(Clone, Debug, Default)]
+    pub struct RedirectContext
+    {
+        pairs: Vec<(RawFd, RawFd)>,
+    }
+
+    impl RedirectContext {
+        /// Creates an empty context.
+        pub fn new() -> RedirectContext {
+            return RedirectContext::default();
+        }
+
+        /// Adds a redirection of `src_fd` to the file `dst_fd` refers to.
+        pub fn add(mut self, src_fd: RawFd, dst_fd: RawFd) -> RedirectContext {
+            self.pairs.push((src_fd, dst_fd));
+            return self;
+        }
+
+        /// Saves all sources, then applies the redirections in the order they were added.
+        ///
+        /// # Returns
+        /// - `io::Result<AppliedContext>`: A guard restoring all sources when dropped if successful,
+        ///   `Err` with the error of the failed redirection otherwise. On error, the redirections
+        ///   applied so far have been rolled back on a best-effort basis.
+        pub fn apply(self) -> io::Result<AppliedContext> {
+            let mut saved = Vec::with_capacity(self.pairs.len());
+            for &(src_fd, _) in &self.pairs {
+                saved.push((dup_cloexec(src_fd)?, src_fd));
+            }
+
+            let mut applied = AppliedContext { entries: Vec::with_capacity(saved.len()) };
+            for (&(_, dst_fd), entry) in self.pairs.iter().zip(saved) {
+                let src_fd = entry.1;
+                applied.entries.push(entry);
+                if let Err(e) = libc_common::redirect_fd_to_fd(src_fd, dst_fd) {
+                    let _ = applied.restore();
+                    return Err(e);
+                }
+            }
+            return Ok(applied);
+        }
+    }
+
+    /// Redirections applied by [`RedirectContext::apply`], restored in reverse order when dropped.
+    #[derive(Debug)]
+    pub struct AppliedContext
+    {
+        entries: Vec<(OwnedFd, RawFd)>,
+    }
+
+    impl AppliedContext {
+        fn restore(&mut self) -> io::Result<()> {
+            let mut result = Ok(());
+            while let Some((saved, src_fd)) = self.entries.pop() {
+                if let Err(e) = libc_common::redirect_fd_to_fd(src_fd, saved.as_raw_fd()) {
+                    result = result.and(Err(e));
+                }
+            }
+            return result;
+        }
+    }
+
+    impl Drop for AppliedContext {
+        fn drop(&mut self) {
+            let _ = self.restore();
+        }
+    }
+
+    /// Saved destinations of stdout and stderr, returned by [`save_std`].
+    ///
+    /// Pass it to [`restore_std`] to restore them, possibly from another scope than the one that
+    /// saved them. If it is dropped instead, the destinations are restored as a safety net.
+    #[derive(Debug)]
+    pub struct SavedStd
+    {
+        stdout_fd: Option<OwnedFd>,
+        stderr_fd: Option<OwnedFd>,
+    }
+
+    impl SavedStd {
+        /// Abandons the restore, leaving stdout and stderr as they are, and closes the saved duplicates.
+        pub fn discard(mut self) {
+            self.stdout_fd.take();
+            self.stderr_fd.take();
+        }
+
+        fn restore(&mut self) -> io::Result<()> {
+            let mut result = Ok(());
+            if let Some(saved) = self.stdout_fd.take() {
+                result = result.and(libc_common::redirect_fd_to_fd(libc::STDOUT_FILENO, saved.as_raw_fd()));
+            }
+            if let Some(saved) = self.stderr_fd.take() {
+                result = result.and(libc_common::redirect_fd_to_fd(libc::STDERR_FILENO, saved.as_raw_fd()));
+            }
+            return result;
+        }
+    }
+
+    impl Drop for SavedStd {
+        fn drop(&mut self) {
+            let _ = self.restore();
+        }
+    }
+
+    /// Saves the current destinations of stdout and stderr.
+    ///
+    /// Unlike [`RedirectGuard`], the returned value is restored explicitly with [`restore_std`].
+    ///
+    /// # Returns
+    /// - `io::Result<SavedStd>`: The saved destinations if successful, `Err` otherwise.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use io_redirect::{redirect_std_to_null, restore_std, save_std};
+    ///
+    /// let saved = save_std().unwrap();
+    /// redirect_std_to_null(false).unwrap();
+    /// println!("goes nowhere");
+    /// restore_std(saved).unwrap();
+    /// println!("goes to the original stdout");
+    /// ```
+    pub fn save_std() -> io::Result<SavedStd> {
+        let stdout_fd = dup_cloexec(libc::STDOUT_FILENO)?;
+        let stderr_fd = dup_cloexec(libc::STDERR_FILENO)?;
+        return Ok(SavedStd { stdout_fd: Some(stdout_fd), stderr_fd: Some(stderr_fd) });
+    }
+
+    /// Restores stdout and stderr to the destinations saved by [`save_std`].
+    ///
+    /// # Parameters
+    /// - `saved`: The destinations to restore.
+    ///
+    /// # Returns
+    /// - `io::Result<()>`: `Ok` if successful, `Err` otherwise. A failure to restore stdout does not
+    ///   prevent stderr from being restored.
+    pub fn restore_std(mut saved: SavedStd) -> io::Result<()> {
+        return saved.restore();
+    }
+
+    /// A redirection that is undone by a timer thread, returned by [`redirect_for_duration`].
+    ///
+    /// Dropping it blocks until the timer expires and the original destination is restored. Use
+    /// [`TimedRedirectHandle::cancel_and_restore`] to restore earlier.
+    #[derive(Debug)]
+    pub struct TimedRedirectHandle
+    {
+        cancelled: std::sync::Arc<(std::sync::Mutex<bool>, std::sync::Condvar)>,
+        timer: Option<std::thread::JoinHandle<io::Result<()>>>,
+    }
+
+    impl TimedRedirectHandle {
+        /// Stops the timer and restores the original destination right away.
+        ///
+        /// # Returns
+        /// - `io::Result<()>`: `Ok` if successful, the error of the restoring `dup2` otherwise.
+        pub fn cancel_and_restore(mut self) -> io::Result<()> {
+            let (cancelled, wakeup) = &*self.cancelled;
+            *cancelled.lock().unwrap_or_else(|e| e.into_inner()) = true;
+            wakeup.notify_one();
+            return self.join();
+        }
+
+        fn join(&mut self) -> io::Result<()> {
+            if let Some(timer) = self.timer.take() {
+                return timer.join().unwrap_or_else(|_| Err(io::Error::other("timer thread panicked")));
+            }
+            return Ok(());
+        }
+    }
+
+    impl Drop for TimedRedirectHandle {
+        fn drop(&mut self) {
+            let _ = self.join();
+        }
+    }
+
+    /// Redirects `src_fd` to `dst_fd` now and restores the original destination after `duration`.
+    ///
+    /// This suits suppressing output for a window of known length, such as a noisy initialization
+    /// step. A background thread waits for `duration` and then restores `src_fd`. The duration is
+    /// measured in elapsed real time, not in CPU time of this process.
+    ///
+    /// # Parameters
+    /// - `src_fd`: The descriptor to redirect.
+    /// - `dst_fd`: The descriptor to redirect to. Only needs to stay open until this function returns.
+    /// - `duration`: How long the redirection lasts.
+    ///
+    /// # Returns
+    /// - `io::Result<TimedRedirectHandle>`: A handle to the timer if successful, `Err` otherwise.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use io_redirect::redirect_for_duration;
+    /// use std::os::fd::AsRawFd;
+    /// use std::time::Duration;
+    ///
+    /// let null = std::fs::File::create("/dev/null").unwrap();
+    /// let handle = redirect_for_duration(libc::STDOUT_FILENO, null.as_raw_fd(), Duration::from_secs(1)).unwrap();
+    /// println!("suppressed for up to a second");
+    /// handle.cancel_and_restore().unwrap();
+    /// ```
+    pub fn redirect_for_duration(src_fd: RawFd, dst_fd: RawFd, duration: std::time::Duration) -> io::Result<TimedRedirectHandle> {
+        let saved = dup_cloexec(src_fd)?;
+        libc_common::redirect_fd_to_fd(src_fd, dst_fd)?;
+
+        let cancelled = std::sync::Arc::new((std::sync::Mutex::new(false), std::sync::Condvar::new()));
+        let timer_state = cancelled.clone();
+        let timer = std::thread::spawn(move || {
+            let (cancelled, wakeup) = &*timer_state;
+            let guard = cancelled.lock().unwrap_or_else(|e| e.into_inner());
+            let _ = wakeup.wait_timeout_while(guard, duration, |cancelled| !*cancelled);
+            return libc_common::redirect_fd_to_fd(src_fd, saved.as_raw_fd());
+        });
+
+        return Ok(TimedRedirectHandle { cancelled, timer: Some(timer) });
+    }
+}
+
+#[cfg(all(unix, feature = "libc_on_unix"))]
+pub use guard::*;
+
+#[cfg(all(unix, feature = "libc_on_unix"))]
+mod builder
+{
+    use super::*;
+    use std::fs::OpenOptions;
+    use std::os::fd::{AsRawFd, RawFd};
+    use std::path::{Path, PathBuf};
+
+    /// A destination or source for one of the standard streams in a [`RedirectBuilder`].
+    #[derive(Debug)]
+    pub enum RedirectTarget
+    {
+        /// An already opened file.
+        File(File),
+        /// A file opened when the redirections are applied.
+        Path(PathBuf),
+        /// The null device.
+        Null,
+        /// An open descriptor owned by the caller.
+        Fd(RawFd),
+    }
+
+    impl From<File> for RedirectTarget {
+        fn from(file: File) -> RedirectTarget {
+            return RedirectTarget::File(file);
+        }
+    }
+
+    impl From<PathBuf> for RedirectTarget {
+        fn from(path: PathBuf) -> RedirectTarget {
+            return RedirectTarget::Path(path);
+        }
+    }
+
+    impl From<&Path> for RedirectTarget {
+        fn from(path: &Path) -> RedirectTarget {
+            return RedirectTarget::Path(path.to_path_buf());
+        }
+    }
+
+    impl From<&str> for RedirectTarget {
+        fn from(path: &str) -> RedirectTarget {
+            return RedirectTarget::Path(PathBuf::from(path));
+        }
+    }
+
+    impl From<RawFd> for RedirectTarget {
+        fn from(fd: RawFd) -> RedirectTarget {
+            return RedirectTarget::Fd(fd);
+        }
+    }
+
+    /// Configures redirections of several standard streams and applies them together.
+    ///
+    /// Every file is opened before any stream is redirected, and the redirections are applied
+    /// through a [`RedirectContext`], so either all of them take effect or none does.
+    ///
+    /// # Examples
+    /// Send stdout to a log file, discard stderr and read stdin from a file:
+    /// ```no_run
+    /// use io_redirect::{RedirectBuilder, RedirectTarget};
+    ///
+    /// let applied = RedirectBuilder::new()
+    ///     .stdout_to("/var/log/app/out.log")
+    ///     .stderr_to(RedirectTarget::Null)
+    ///     .stdin_from("input.txt")
+    ///     .create_parents(true)
+    ///     .apply()
+    ///     .unwrap();
+    /// println!("goes to out.log");
+    /// drop(applied);
+    /// println!("goes to the original stdout");
+    /// ```
+    ///
+    /// Send stdout and stderr to wherever an existing descriptor points, without reopening it:
+    /// ```no_run
+    /// use io_redirect::RedirectBuilder;
+    /// use std::os::fd::AsRawFd;
+    ///
+    /// let log = std::fs::File::create("log.txt").unwrap();
+    /// let applied = RedirectBuilder::new().stdout_to(log.as_raw_fd()).stderr_to(log.as_raw_fd()).apply().unwrap();
+    /// ```
+    #[derive(Debug)]
+    pub struct RedirectBuilder
+    {
+        stdin: Option<RedirectTarget>,
+        stdout: Option<RedirectTarget>,
+        stderr: Option<RedirectTarget>,
+        append: bool,
+        create_parents: bool,
+        cloexec: bool,
+    }
+
+    impl Default for RedirectBuilder {
+        fn default() -> RedirectBuilder {
+            return RedirectBuilder { stdin: None, stdout: None, stderr: None, append: true, create_parents: false, cloexec: true };
+        }
+    }
+
+    impl RedirectBuilder {
+        /// Creates a builder that redirects nothing, appends to files and does not create directories.
+        pub fn new() -> RedirectBuilder {
+            return RedirectBuilder::default();
+        }
+
+        /// Redirects stdout to `target`.
+        pub fn stdout_to(mut self, target: impl Into<RedirectTarget>) -> RedirectBuilder {
+            self.stdout = Some(target.into());
+            return self;
+        }
+
+        /// Redirects stderr to `target`.
+        pub fn stderr_to(mut self, target: impl Into<RedirectTarget>) -> RedirectBuilder {
+            self.stderr = Some(target.into());
+            return self;
+        }
+
+        /// Makes stdin read from `target`.
+        pub fn stdin_from(mut self, target: impl Into<RedirectTarget>) -> RedirectBuilder {
+            self.stdin = Some(target.into());
+            return self;
+        }
+
+        /// Sets whether files given by path are appended to rather than truncated. Defaults to `true`.
+        pub fn append(mut self, append: bool) -> RedirectBuilder {
+            self.append = append;
+            return self;
+        }
+
+        /// Sets whether missing parent directories of files given by path are created. Defaults to `false`.
+        pub fn create_parents(mut self, create_parents: bool) -> RedirectBuilder {
+            self.create_parents = create_parents;
+            return self;
+        }
+
+        /// Sets whether files opened by the builder are close-on-exec until they are redirected to.
+        /// Defaults to `true`. See [`RedirectOptions::cloexec_intermediate`].
+        pub fn cloexec(mut self, cloexec: bool) -> RedirectBuilder {
+            self.cloexec = cloexec;
+            return self;
+        }
+
+        /// Opens all targets and redirects the configured streams.
+        ///
+        /// # Returns
+        /// - `io::Result<AppliedContext>`: A guard restoring all redirected streams when dropped if
+        ///   successful, `Err` otherwise. On error, no stream is left redirected.
+        ///
+        /// # Notes
+        /// The guard is the [`AppliedContext`] of the underlying [`RedirectContext`].
+        pub fn apply(mut self) -> io::Result<AppliedContext> {
+            let opts = RedirectOptions { append: self.append, truncate: !self.append, cloexec_intermediate: self.cloexec, create_parents: self.create_parents, ..Default::default() };
+            let streams = [(libc::STDIN_FILENO, self.stdin.take()), (libc::STDOUT_FILENO, self.stdout.take()), (libc::STDERR_FILENO, self.stderr.take())];
+
+            // The opened files only need to outlive the redirections, which hold their own references.
+            let mut opened = Vec::new();
+            let mut context = RedirectContext::new();
+            for (src_fd, target) in streams {
+                let Some(target) = target else {
+                    continue;
+                };
+                let is_input = src_fd == libc::STDIN_FILENO;
+                let file = match target {
+                    RedirectTarget::File(file) => file,
+                    RedirectTarget::Path(path) if is_input => File::open(path)?,
+                    RedirectTarget::Path(path) => opts.open(&path)?,
+                    RedirectTarget::Null => OpenOptions::new().read(is_input).write(!is_input).open("/dev/null")?,
+                    RedirectTarget::Fd(fd) => {
+                        if fd < 0 {
+                            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("invalid redirect target fd {fd}")));
+                        }
+                        context = context.add(src_fd, fd);
+                        continue;
+                    }
+                };
+                context = context.add(src_fd, file.as_raw_fd());
+                opened.push(file);
+            }
+
+            return context.apply();
+        }
+    }
+}
+
+#[cfg(all(unix, feature = "libc_on_unix"))]
+pub use builder::*;
+
+#[cfg(all(unix, feature = "libc_on_unix"))]
+mod pipes
+{
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+    use std::thread::JoinHandle;
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    use libc::{fcntl, FD_CLOEXEC, F_SETFD};
+
+    /// Creates a pipe, returned as `(read_end, write_end)`.
+    ///
+    /// Both ends are inherited by child processes. Use [`pipe_cloexec`] unless that is the intent.
+    pub fn pipe() -> io::Result<(OwnedFd, OwnedFd)> {
+        let mut fds = [0; 2];
+        if unsafe { libc::pipe(fds.as_mut_ptr()) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        return Ok(unsafe { (OwnedFd::from_raw_fd(fds[0]), OwnedFd::from_raw_fd(fds[1])) });
+    }
+
+    /// Creates a pipe whose ends are not inherited by child processes, returned as `(read_end, write_end)`.
+    ///
+    /// Close-on-exec is set atomically with `pipe2(O_CLOEXEC)` where available. On macOS and iOS it
+    /// is set with `fcntl` right after `pipe`, so a child spawned concurrently from another thread
+    /// may still inherit the ends.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use io_redirect::{pipe_cloexec, Redirectable};
+    ///
+    /// let (read_end, write_end) = pipe_cloexec().unwrap();
+    /// std::io::stdout().redirect(&write_end).unwrap();
+    /// ```
+    pub fn pipe_cloexec() -> io::Result<(OwnedFd, OwnedFd)> {
+        #[cfg(not(any(target_os = "macos", target_os = "ios")))]
+        {
+            let mut fds = [0; 2];
+            if unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_CLOEXEC) } < 0 {
+                return Err(io::Error::last_os_error());
+            }
+            return Ok(unsafe { (OwnedFd::from_raw_fd(fds[0]), OwnedFd::from_raw_fd(fds[1])) });
+        }
+
+        #[cfg(any(target_os = "macos", target_os = "ios"))]
+        {
+            let (read_end, write_end) = pipe()?;
+            for fd in [read_end.as_raw_fd(), write_end.as_raw_fd()] {
+                if unsafe { fcntl(fd, F_SETFD, FD_CLOEXEC) } < 0 {
+                    return Err(io::Error::last_os_error());
+                }
+            }
+            return Ok((read_end, write_end));
+        }
+    }
+
+    /// Standard streams redirected to pipes whose lines are handed to callbacks on background threads.
+    ///
+    /// Dropping it restores the original destinations, so that the threads see end of file, and
+    /// waits for them to finish.
+    #[derive(Debug, Default)]
+    pub(crate) struct LineForwarding
+    {
+        streams: Vec<(RawFd, OwnedFd)>,
+        forwarders: Vec<JoinHandle<()>>,
+    }
+
+    impl LineForwarding {
+        /// Redirects `src_fd` to a new pipe and calls `on_line` with each line read from it, without the newline.
+        pub(crate) fn forward<F: FnMut(Vec<u8>) + Send + 'static>(&mut self, src_fd: RawFd, mut on_line: F) -> io::Result<()> {
+            let original = dup_cloexec(src_fd)?;
+            let (read_end, write_end) = pipe_cloexec()?;
+            libc_common::redirect_fd_to_fd(src_fd, write_end.as_raw_fd())?;
+            self.streams.push((src_fd, original));
+
+            let reader = BufReader::new(File::from(read_end));
+            self.forwarders.push(std::thread::spawn(move || {
+                for line in reader.split(b'\n') {
+                    let Ok(line) = line else {
+                        return;
+                    };
+                    on_line(line);
+                }
+            }));
+            return Ok(());
+        }
+
+        /// Restores the original destinations and waits for the remaining lines to be handled.
+        pub(crate) fn stop(&mut self) {
+            let _ = io::stdout().flush();
+            let _ = io::stderr().flush();
+            for (src_fd, original) in self.streams.drain(..) {
+                let _ = libc_common::redirect_fd_to_fd(src_fd, original.as_raw_fd());
+            }
+            for forwarder in self.forwarders.drain(..) {
+                let _ = forwarder.join();
+            }
+        }
+    }
+
+    impl Drop for LineForwarding {
+        fn drop(&mut self) {
+            self.stop();
+        }
+    }
+}
+
+#[cfg(all(unix, feature = "libc_on_unix"))]
+pub use pipes::{pipe, pipe_cloexec};
+
+#[cfg(all(unix, feature = "libc_on_unix"))]
+mod tee
+{
+    use super::*;
+    use std::io::{Read, Write};
+    use std::os::fd::{AsRawFd, OwnedFd, RawFd};
+    use std::thread::JoinHandle;
+
+    /// Mirrors everything written to a descriptor to both its original destination and a file.
+    ///
+    /// Returned by [`tee_redirect`]. Dropping it restores the original destination, waits for the
+    /// mirrored data to be flushed and stops the background thread.
+    #[derive(Debug)]
+    pub struct TeeRedirect
+    {
+        src_fd: RawFd,
+        original: OwnedFd,
+        forwarder: Option<JoinHandle<io::Result<()>>>,
+    }
+
+    impl Drop for TeeRedirect {
+        fn drop(&mut self) {
+            let _ = libc_common::redirect_fd_to_fd(self.src_fd, self.original.as_raw_fd());
+            if let Some(forwarder) = self.forwarder.take() {
+                let _ = forwarder.join();
+            }
+        }
+    }
+
+    /// Redirects `src_fd` so that writes to it reach both its current destination and `new_dest`.
+    ///
+    /// This behaves like `foo 2>&1 | tee log.txt` without a shell. `src_fd` is redirected to a pipe
+    /// and a background thread copies each chunk read from the pipe to a saved duplicate of the
+    /// original destination and to `new_dest`.
+    ///
+    /// # Parameters
+    /// - `src_fd`: The descriptor to mirror, such as `libc::STDERR_FILENO`.
+    /// - `new_dest`: The file receiving a copy of everything written to `src_fd`.
+    ///
+    /// # Returns
+    /// - `io::Result<TeeRedirect>`: A handle that undoes the redirection on drop if successful,
+    ///   `Err` otherwise.
+    pub fn tee_redirect(src_fd: RawFd, new_dest: &File) -> io::Result<TeeRedirect> {
+        let original = dup_cloexec(src_fd)?;
+        let mut original_copy = File::from(original.try_clone()?);
+        let mut new_dest = new_dest.try_clone()?;
+
+        let (read_end, write_end) = pipes::pipe_cloexec()?;
+        libc_common::redirect_fd_to_fd(src_fd, write_end.as_raw_fd())?;
+        drop(write_end);
+
+        let mut reader = File::from(read_end);
+        let forwarder = std::thread::spawn(move || {
+            let mut buf = [0u8; 8 * 1024];
+            loop {
+                let count = match reader.read(&mut buf) {
+                    Ok(0) => return Ok(()),
+                    Ok(count) => count,
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(e) => return Err(e),
+                };
+                original_copy.write_all(&buf[..count])?;
+                new_dest.write_all(&buf[..count])?;
+            }
+        });
+
+        return Ok(TeeRedirect { src_fd, original, forwarder: Some(forwarder) });
+    }
+
+    /// Mirrors stderr to its current destination and to the file at `log_path`, like
+    /// `foo 2> >(tee error.log)`.
+    ///
+    /// This opens `log_path` with the default [`RedirectOptions`] and passes it to [`tee_redirect`].
+    /// It deliberately returns the existing [`TeeRedirect`] handle rather than a separate handle
+    /// type, so both kinds of tee can be stored and dropped alike.
+    ///
+    /// # Parameters
+    /// - `log_path`: The path of the file receiving a copy of stderr, created if it does not exist.
+    /// - `append`: Whether to append to the file instead of truncating it.
+    ///
+    /// # Returns
+    /// - `io::Result<TeeRedirect>`: A handle that undoes the redirection on drop if successful,
+    ///   `Err` otherwise.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use io_redirect::redirect_stderr_tee;
+    /// use std::path::Path;
+    ///
+    /// let tee = redirect_stderr_tee(Path::new("error.log"), true).unwrap();
+    /// eprintln!("shown on the terminal and logged");
+    /// drop(tee);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn redirect_stderr_tee(log_path: &std::path::Path, append: bool) -> io::Result<TeeRedirect> {
+        let opts = RedirectOptions { append, truncate: !append, ..Default::default() };
+        let log = opts.open(log_path)?;
+        return tee_redirect(libc::STDERR_FILENO, &log);
+    }
+
+    /// Passes everything written to a descriptor to a callback before it reaches its original destination.
+    ///
+    /// Returned by [`intercept_redirect`]. Dropping it restores the original destination, which
+    /// closes the last write end of the pipe, and waits for the background thread to finish.
+    pub struct InterceptWrite<F>
+    {
+        src_fd: RawFd,
+        original: OwnedFd,
+        forwarder: Option<JoinHandle<io::Result<()>>>,
+        _callback: std::marker::PhantomData<F>,
+    }
+
+    impl<F> std::fmt::Debug for InterceptWrite<F> {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            return f.debug_struct("InterceptWrite").field("src_fd", &self.src_fd).field("original", &self.original).field("forwarder", &self.forwarder).finish();
+        }
+    }
+
+    impl<F> Drop for InterceptWrite<F> {
+        fn drop(&mut self) {
+            let _ = libc_common::redirect_fd_to_fd(self.src_fd, self.original.as_raw_fd());
+            if let Some(forwarder) = self.forwarder.take() {
+                let _ = forwarder.join();
+            }
+        }
+    }
+
+    /// Redirects `src_fd` so that `callback` sees each chunk written to it before it is forwarded
+    /// to its current destination.
+    ///
+    /// This suits real-time processing of output such as rate limiting, parsing or metrics. `src_fd`
+    /// is redirected to a pipe and a background thread calls `callback` with each chunk read from the
+    /// pipe, then copies the chunk to a saved duplicate of the original destination. Chunks follow
+    /// the pipe reads, so a single write may be split and several writes may be merged.
+    ///
+    /// # Parameters
+    /// - `src_fd`: The descriptor to intercept, such as `libc::STDOUT_FILENO`.
+    /// - `callback`: Called on the background thread with each chunk.
+    ///
+    /// # Returns
+    /// - `io::Result<InterceptWrite<F>>`: A handle that undoes the redirection on drop if successful,
+    ///   `Err` otherwise.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use io_redirect::intercept_redirect;
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use std::sync::Arc;
+    ///
+    /// let lines = Arc::new(AtomicUsize::new(0));
+    /// let counter = lines.clone();
+    /// let intercept = intercept_redirect(libc::STDOUT_FILENO, move |chunk| {
+    ///     counter.fetch_add(chunk.iter().filter(|&&b| b == b'\n').count(), Ordering::Relaxed);
+    /// }).unwrap();
+    /// println!("counted");
+    /// drop(intercept);
+    /// assert_eq!(lines.load(Ordering::Relaxed), 1);
+    /// ```
+    pub fn intercept_redirect<F: Fn(&[u8]) + Send + 'static>(src_fd: RawFd, callback: F) -> io::Result<InterceptWrite<F>> {
+        let original = dup_cloexec(src_fd)?;
+        let mut original_copy = File::from(original.try_clone()?);
+
+        let (read_end, write_end) = pipes::pipe_cloexec()?;
+        libc_common::redirect_fd_to_fd(src_fd, write_end.as_raw_fd())?;
+        drop(write_end);
+
+        let mut reader = File::from(read_end);
+        let forwarder = std::thread::spawn(move || {
+            let mut buf = [0u8; 8 * 1024];
+            loop {
+                let count = match reader.read(&mut buf) {
+                    Ok(0) => return Ok(()),
+                    Ok(count) => count,
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(e) => return Err(e),
+                };
+                callback(&buf[..count]);
+                original_copy.write_all(&buf[..count])?;
+            }
+        });
+
+        return Ok(InterceptWrite { src_fd, original, forwarder: Some(forwarder), _callback: std::marker::PhantomData });
+    }
+
+    /// A redirection set up by [`redirect_with_fallback`] or [`redirect_writer_with_fallback`].
+    ///
+    /// Dropping it restores the original destination and waits for the copy thread, if any.
+    #[derive(Debug)]
+    pub struct FallbackHandle
+    {
+        src_fd: RawFd,
+        original: Option<OwnedFd>,
+        copier: Option<WriterRedirect<Box<dyn Write + Send>>>,
+    }
+
+    impl FallbackHandle {
+        /// Returns whether the output is copied by a background thread rather than redirected with `dup2`.
+        pub fn is_fallback(&self) -> bool {
+            return self.copier.is_some();
+        }
+
+        /// Restores the original destination and waits for the copy thread to drain the pipe.
+        ///
+        /// # Returns
+        /// - `io::Result<()>`: `Ok` if successful, the error of the restoring `dup2` or of the copy
+        ///   thread otherwise.
+        pub fn finish(mut self) -> io::Result<()> {
+            if let Some(copier) = self.copier.take() {
+                return copier.finish().map(|_| ());
+            }
+            return self.restore();
+        }
+
+        fn restore(&mut self) -> io::Result<()> {
+            if let Some(original) = &self.original {
+                libc_common::redirect_fd_to_fd(self.src_fd, original.as_raw_fd())?;
+                self.original = None;
+            }
+            return Ok(());
+        }
+    }
+
+    impl Drop for FallbackHandle {
+        fn drop(&mut self) {
+            let _ = self.restore();
+        }
+    }
+
+    /// Redirects `src_fd` to `dest`, falling back to copying through a pipe if `dup2` is rejected.
+    ///
+    /// `dup2` onto the descriptor of `dest` is tried first. If it fails with `ENOSYS` or `EINVAL`,
+    /// as it may in sandboxes that filter syscalls, `src_fd` is redirected to a pipe instead and a
+    /// background thread copies everything read from the pipe to `dest` with `io::copy`, like
+    /// [`redirect_to_writer`]. Destinations without a descriptor go through
+    /// [`redirect_writer_with_fallback`].
+    ///
+    /// # Parameters
+    /// - `src_fd`: The descriptor to redirect.
+    /// - `dest`: The destination, moved to the copy thread when falling back.
+    ///
+    /// # Returns
+    /// - `io::Result<FallbackHandle>`: A handle that undoes the redirection if successful, `Err`
+    ///   otherwise.
+    pub fn redirect_with_fallback<W: Write + AsRawFd + Send + 'static>(src_fd: RawFd, dest: W) -> io::Result<FallbackHandle> {
+        let original = dup_cloexec(src_fd)?;
+        match libc_common::redirect_fd_to_fd(src_fd, dest.as_raw_fd()) {
+            Ok(()) => return Ok(FallbackHandle { src_fd, original: Some(original), copier: None }),
+            Err(e) if matches!(e.raw_os_error(), Some(libc::ENOSYS) | Some(libc::EINVAL)) => {}
+            Err(e) => return Err(e),
+        }
+        drop(original);
+        return redirect_writer_with_fallback(src_fd, dest);
+    }
+
+    /// Redirects `src_fd` to a destination without a descriptor by copying through a pipe.
+    ///
+    /// This is the copying path of [`redirect_with_fallback`] for writers such as a `BufWriter` or
+    /// a compressing encoder. It returns the same [`FallbackHandle`], so callers can treat both
+    /// kinds of destination alike.
+    ///
+    /// # Parameters
+    /// - `src_fd`: The descriptor to redirect.
+    /// - `dest`: The destination, moved to the copy thread.
+    ///
+    /// # Returns
+    /// - `io::Result<FallbackHandle>`: A handle that undoes the redirection if successful, `Err`
+    ///   otherwise.
+    pub fn redirect_writer_with_fallback<W: Write + Send + 'static>(src_fd: RawFd, dest: W) -> io::Result<FallbackHandle> {
+        let copier = redirect_to_writer(src_fd, Box::new(dest) as Box<dyn Write + Send>)?;
+        return Ok(FallbackHandle { src_fd, original: None, copier: Some(copier) });
+    }
+
+    /// Feeds everything written to a descriptor into a `Write` implementation until finished.
+    ///
+    /// Returned by [`redirect_to_writer`]. Dropping it restores the original destination and waits
+    /// for the copy thread, discarding the writer. If restoring fails, the copy thread would never
+    /// see end of file, so it is detached instead.
+    pub struct WriterRedirect<W>
+    {
+        src_fd: RawFd,
+        original: Option<OwnedFd>,
+        copier: Option<JoinHandle<io::Result<W>>>,
+    }
+
+    impl<W> std::fmt::Debug for WriterRedirect<W> {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            return f.debug_struct("WriterRedirect").field("src_fd", &self.src_fd).field("original", &self.original).field("copier", &self.copier).finish();
+        }
+    }
+
+    impl<W> WriterRedirect<W> {
+        /// Restores the original destination, waits for the copy thread to drain the pipe and
+        /// returns the writer, e.g. to call `finish` on a compressing encoder.
+        ///
+        /// # Returns
+        /// - `io::Result<W>`: The flushed writer if successful, the error of the restoring `dup2`
+        ///   or of the copy thread otherwise.
+        pub fn finish(mut self) -> io::Result<W> {
+            self.restore()?;
+            return match self.copier.take() {
+                Some(copier) => copier.join().unwrap_or_else(|_| Err(io::Error::other("copy thread panicked"))),
+                None => Err(io::Error::other("copy thread already joined")),
+            };
+        }
+
+        fn restore(&mut self) -> io::Result<()> {
+            if let Some(original) = &self.original {
+                libc_common::redirect_fd_to_fd(self.src_fd, original.as_raw_fd())?;
+                self.original = None;
+            }
+            return Ok(());
+        }
+    }
+
+    impl<W> Drop for WriterRedirect<W> {
+        fn drop(&mut self) {
+            if self.restore().is_ok() {
+                if let Some(copier) = self.copier.take() {
+                    let _ = copier.join();
+                }
+            }
+        }
+    }
+
+    /// Redirects `src_fd` so that everything written to it is passed to `writer`.
+    ///
+    /// This makes any `Write` implementation a redirect destination, such as a compressing encoder
+    /// or a network client. `src_fd` is redirected to a pipe and a background thread copies
+    /// everything read from the pipe to `writer` with `io::copy`.
+    ///
+    /// # Parameters
+    /// - `src_fd`: The descriptor to redirect, such as `libc::STDOUT_FILENO`.
+    /// - `writer`: The destination, moved to the copy thread until [`WriterRedirect::finish`].
+    ///
+    /// # Returns
+    /// - `io::Result<WriterRedirect<W>>`: A handle that undoes the redirection if successful, `Err`
+    ///   otherwise.
+    ///
+    /// # Notes
+    /// The copy thread only sees end of file once no descriptor refers to the write end of the
+    /// pipe, so `src_fd` should not be duplicated while redirected. Flush buffered writers such as
+    /// `std::io::stdout()` before finishing.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use io_redirect::redirect_to_writer;
+    /// use std::io::Write;
+    ///
+    /// let redirect = redirect_to_writer(libc::STDOUT_FILENO, Vec::new()).unwrap();
+    /// println!("collected");
+    /// std::io::stdout().flush().unwrap();
+    /// assert_eq!(redirect.finish().unwrap(), b"collected\n");
+    /// ```
+    pub fn redirect_to_writer<W: Write + Send + 'static>(src_fd: RawFd, mut writer: W) -> io::Result<WriterRedirect<W>> {
+        let original = dup_cloexec(src_fd)?;
+        let (read_end, write_end) = pipes::pipe_cloexec()?;
+        libc_common::redirect_fd_to_fd(src_fd, write_end.as_raw_fd())?;
+        drop(write_end);
+
+        let mut reader = File::from(read_end);
+        let copier = std::thread::spawn(move || {
+            io::copy(&mut reader, &mut writer)?;
+            writer.flush()?;
+            return Ok(writer);
+        });
+
+        return Ok(WriterRedirect { src_fd, original: Some(original), copier: Some(copier) });
+    }
+}
+
+#[cfg(all(unix, feature = "libc_on_unix"))]
+pub use tee::*;
+
+#[cfg(all(unix, feature = "libc_on_unix"))]
+mod capture
+{
+    use super::*;
+    use std::io::{Read, Write};
+    use std::os::fd::{AsRawFd, OwnedFd, RawFd};
+    use std::thread::JoinHandle;
+    use libc::{STDERR_FILENO, STDOUT_FILENO};
+
+    /// Captures everything written to a standard stream until finished or dropped.
+    ///
+    /// Returned by [`capture_stdout`] and [`capture_stderr`]. The captured bytes are drained from
+    /// the pipe by a background thread, so the writer never blocks on a full pipe.
+    #[derive(Debug)]
+    pub struct CaptureGuard
+    {
+        src_fd: RawFd,
+        original: Option<OwnedFd>,
+        reader: Option<JoinHandle<io::Result<Vec<u8>>>>,
+    }
+
+    impl CaptureGuard {
+        /// Restores the stream and returns everything written to it since the capture started.
+        pub fn finish(mut self) -> io::Result<Vec<u8>> {
+            self.restore()?;
+            return match self.reader.take() {
+                Some(reader) => reader.join().unwrap_or_else(|_| Err(io::Error::other("capture thread panicked"))),
+                None => Ok(Vec::new()),
+            };
+        }
+
+        fn restore(&mut self) -> io::Result<()> {
+            let _ = io::stdout().flush();
+            let _ = io::stderr().flush();
+            if let Some(original) = self.original.take() {
+                libc_common::redirect_fd_to_fd(self.src_fd, original.as_raw_fd())?;
+            }
+            return Ok(());
+        }
+    }
+
+    impl Drop for CaptureGuard {
+        fn drop(&mut self) {
+            let _ = self.restore();
+            if let Some(reader) = self.reader.take() {
+                let _ = reader.join();
+            }
+        }
+    }
+
+    /// Starts capturing everything written to stdout.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// let capture = io_redirect::capture_stdout().unwrap();
+    /// print!("hello");
+    /// assert_eq!(capture.finish().unwrap(), b"hello");
+    /// ```
+    pub fn capture_stdout() -> io::Result<CaptureGuard> {
+        return capture_fd(STDOUT_FILENO);
+    }
+
+    /// Starts capturing everything written to stderr.
+    pub fn capture_stderr() -> io::Result<CaptureGuard> {
+        return capture_fd(STDERR_FILENO);
+    }
+
+    /// Runs `f` while capturing everything written to `fd`, and returns the captured bytes with the result of `f`.
+    ///
+    /// `fd` is redirected to a pipe drained by a background thread for the duration of `f`. If `f`
+    /// panics, `fd` is restored while unwinding and the captured bytes are discarded.
+    ///
+    /// # Parameters
+    /// - `fd`: The descriptor to capture, such as `libc::STDOUT_FILENO`.
+    /// - `f`: The closure to run while `fd` is captured.
+    ///
+    /// # Returns
+    /// - `io::Result<(Vec<u8>, R)>`: The captured bytes and the result of `f` if successful, `Err`
+    ///   otherwise.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use io_redirect::capture_fd_output;
+    ///
+    /// let (captured, answer) = capture_fd_output(libc::STDOUT_FILENO, || {
+    ///     print!("computing");
+    ///     42
+    /// }).unwrap();
+    /// assert_eq!(captured, b"computing");
+    /// assert_eq!(answer, 42);
+    /// ```
+    pub fn capture_fd_output<F: FnOnce() -> R, R>(fd: RawFd, f: F) -> io::Result<(Vec<u8>, R)> {
+        let capture = capture_fd(fd)?;
+        let result = f();
+        return Ok((capture.finish()?, result));
+    }
+
+    fn capture_fd(src_fd: RawFd) -> io::Result<CaptureGuard> {
+        let _ = io::stdout().flush();
+        let original = dup_cloexec(src_fd)?;
+        let (read_end, write_end) = pipes::pipe_cloexec()?;
+        libc_common::redirect_fd_to_fd(src_fd, write_end.as_raw_fd())?;
+        drop(write_end);
+
+        let mut read_end = File::from(read_end);
+        let reader = std::thread::spawn(move || {
+            let mut captured = Vec::new();
+            read_end.read_to_end(&mut captured)?;
+            return Ok(captured);
+        });
+
+        return Ok(CaptureGuard { src_fd, original: Some(original), reader: Some(reader) });
+    }
+
+    /// A descriptor redirected to a pipe whose read end is available to the caller.
+    ///
+    /// Returned by [`pipe_redirect`]. Unlike [`CaptureGuard`], nothing drains the pipe in the
+    /// background, so the output can be read incrementally through [`PipeRedirect::read_end`].
+    /// Writes to the descriptor block once the pipe buffer is full until it is read from.
+    /// Dropping it restores the original destination.
+    #[derive(Debug)]
+    pub struct PipeRedirect
+    {
+        src_fd: RawFd,
+        original: Option<OwnedFd>,
+        read_end: File,
+    }
+
+    impl PipeRedirect {
+        /// Returns the read end of the pipe for incremental reads.
+        pub fn read_end(&self) -> &File {
+            return &self.read_end;
+        }
+
+        /// Restores the original destination and returns everything that has not been read yet.
+        pub fn finish(mut self) -> io::Result<Vec<u8>> {
+            self.restore()?;
+            let mut remaining = Vec::new();
+            self.read_end.read_to_end(&mut remaining)?;
+            return Ok(remaining);
+        }
+
+        fn restore(&mut self) -> io::Result<()> {
+            if let Some(original) = self.original.take() {
+                libc_common::redirect_fd_to_fd(self.src_fd, original.as_raw_fd())?;
+            }
+            return Ok(());
+        }
+    }
+
+    impl Drop for PipeRedirect {
+        fn drop(&mut self) {
+            let _ = self.restore();
+        }
+    }
+
+    /// Redirects `src_fd` to a new pipe and returns a handle to read what is written to it.
+    ///
+    /// # Parameters
+    /// - `src_fd`: The descriptor to redirect.
+    ///
+    /// # Returns
+    /// - `io::Result<PipeRedirect>`: A handle owning the read end of the pipe if successful, `Err` otherwise.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use io_redirect::pipe_redirect;
+    /// use std::io::Read;
+    ///
+    /// let redirect = pipe_redirect(2).unwrap();
+    /// eprint!("hello");
+    /// let mut buf = [0u8; 5];
+    /// redirect.read_end().read_exact(&mut buf).unwrap();
+    /// assert_eq!(&buf, b"hello");
+    /// ```
+    pub fn pipe_redirect(src_fd: RawFd) -> io::Result<PipeRedirect> {
+        let original = dup_cloexec(src_fd)?;
+        let (read_end, write_end) = pipes::pipe_cloexec()?;
+        libc_common::redirect_fd_to_fd(src_fd, write_end.as_raw_fd())?;
+        drop(write_end);
+        return Ok(PipeRedirect { src_fd, original: Some(original), read_end: File::from(read_end) });
+    }
+
+    /// A descriptor redirected to memory, returned by [`redirect_to_cursor`].
+    ///
+    /// Dropping it restores the original destination and discards the captured bytes.
+    #[derive(Debug)]
+    pub struct MemRedirect
+    {
+        src_fd: RawFd,
+        original: Option<OwnedFd>,
+        backing: MemBacking,
+    }
+
+    #[derive(Debug)]
+    enum MemBacking
+    {
+        #[cfg(all(target_os = "linux", feature = "linux-memfd"))]
+        Memfd(File),
+        #[cfg(not(all(target_os = "linux", feature = "linux-memfd")))]
+        Pipe { captured: std::sync::Arc<std::sync::Mutex<Vec<u8>>>, reader: Option<JoinHandle<io::Result<()>>> },
+    }
+
+    impl MemRedirect {
+        /// Returns a copy of everything captured so far.
+        ///
+        /// # Notes
+        /// Without a memory file the bytes are collected by a background thread, so the most recent
+        /// writes may not be visible yet. [`MemRedirect::finish`] always returns all of them.
+        pub fn get(&self) -> Vec<u8> {
+            return match &self.backing {
+                #[cfg(all(target_os = "linux", feature = "linux-memfd"))]
+                MemBacking::Memfd(file) => read_memfd(file).unwrap_or_default(),
+                #[cfg(not(all(target_os = "linux", feature = "linux-memfd")))]
+                MemBacking::Pipe { captured, .. } => captured.lock().unwrap_or_else(|e| e.into_inner()).clone(),
+            };
+        }
+
+        /// Restores the original destination and returns everything captured.
+        pub fn finish(mut self) -> io::Result<Vec<u8>> {
+            self.restore()?;
+            return match &mut self.backing {
+                #[cfg(all(target_os = "linux", feature = "linux-memfd"))]
+                MemBacking::Memfd(file) => read_memfd(file),
+                #[cfg(not(all(target_os = "linux", feature = "linux-memfd")))]
+                MemBacking::Pipe { captured, reader } => {
+                    if let Some(reader) = reader.take() {
+                        reader.join().unwrap_or_else(|_| Err(io::Error::other("capture thread panicked")))?;
+                    }
+                    Ok(std::mem::take(&mut *captured.lock().unwrap_or_else(|e| e.into_inner())))
+                }
+            };
+        }
+
+        fn restore(&mut self) -> io::Result<()> {
+            if let Some(original) = self.original.take() {
+                libc_common::redirect_fd_to_fd(self.src_fd, original.as_raw_fd())?;
+            }
+            return Ok(());
+        }
+    }
+
+    impl Drop for MemRedirect {
+        fn drop(&mut self) {
+            let _ = self.restore();
+            #[cfg(not(all(target_os = "linux", feature = "linux-memfd")))]
+            {
+                let MemBacking::Pipe { reader, .. } = &mut self.backing;
+                if let Some(reader) = reader.take() {
+                    let _ = reader.join();
+                }
+            }
+        }
+    }
+
+    /// Reads the whole memory file without moving the offset it shares with the redirected descriptor.
+    #[cfg(all(target_os = "linux", feature = "linux-memfd"))]
+    fn read_memfd(file: &File) -> io::Result<Vec<u8>> {
+        use std::os::unix::fs::FileExt;
+
+        let mut contents = vec![0u8; file.metadata()?.len() as usize];
+        file.read_exact_at(&mut contents, 0)?;
+        return Ok(contents);
+    }
+
+    /// Redirects `src_fd` to memory so that everything written to it can be retrieved as bytes.
+    ///
+    /// On Linux with the `linux-memfd` feature, the bytes are stored in an anonymous memory file.
+    /// Otherwise `src_fd` is redirected to a pipe drained by a background thread.
+    ///
+    /// # Parameters
+    /// - `src_fd`: The descriptor to capture.
+    ///
+    /// # Returns
+    /// - `io::Result<MemRedirect>`: A handle to the captured bytes if successful, `Err` otherwise.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use io_redirect::redirect_to_cursor;
+    ///
+    /// let capture = redirect_to_cursor(2).unwrap();
+    /// eprint!("hello");
+    /// assert_eq!(capture.finish().unwrap(), b"hello");
+    /// ```
+    pub fn redirect_to_cursor(src_fd: RawFd) -> io::Result<MemRedirect> {
+        let original = dup_cloexec(src_fd)?;
+
+        #[cfg(all(target_os = "linux", feature = "linux-memfd"))]
+        let backing = {
+            let file = unix::create_memfd(c"io-redirect", libc::MFD_CLOEXEC)?;
+            libc_common::redirect_fd_to_fd(src_fd, file.as_raw_fd())?;
+            MemBacking::Memfd(file)
+        };
+
+        #[cfg(not(all(target_os = "linux", feature = "linux-memfd")))]
+        let backing = {
+            let (read_end, write_end) = pipes::pipe_cloexec()?;
+            libc_common::redirect_fd_to_fd(src_fd, write_end.as_raw_fd())?;
+            drop(write_end);
+
+            let captured = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+            let sink = captured.clone();
+            let mut read_end = File::from(read_end);
+            let reader = std::thread::spawn(move || {
+                let mut buf = [0u8; 8 * 1024];
+                loop {
+                    let count = match read_end.read(&mut buf) {
+                        Ok(0) => return Ok(()),
+                        Ok(count) => count,
+                        Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                        Err(e) => return Err(e),
+                    };
+                    sink.lock().unwrap_or_else(|e| e.into_inner()).extend_from_slice(&buf[..count]);
+                }
+            });
+            MemBacking::Pipe { captured, reader: Some(reader) }
+        };
+
+        return Ok(MemRedirect { src_fd, original: Some(original), backing });
+    }
+}
+
+#[cfg(all(unix, feature = "libc_on_unix"))]
+pub use capture::*;
+
+#[cfg(all(unix, feature = "libc_on_unix"))]
+mod stdin_bytes
+{
+    use super::*;
+    use std::io::Write;
+    use std::os::fd::AsRawFd;
+    use std::thread::JoinHandle;
+    use libc::STDIN_FILENO;
+
+    /// Feeds bytes into stdin from a background thread.
+    ///
+    /// Returned by [`redirect_stdin_from_bytes`]. Stdin reaches end of file once all bytes are read.
+    #[derive(Debug)]
+    pub struct StdinBytesHandle
+    {
+        writer: JoinHandle<io::Result<()>>,
+    }
+
+    impl StdinBytesHandle {
+        /// Waits until all bytes have been written and reports whether writing them failed.
+        pub fn join(self) -> io::Result<()> {
+            return self.writer.join().unwrap_or_else(|_| Err(io::Error::other("stdin writer thread panicked")));
+        }
+    }
+
+    /// Redirects stdin so that it reads `data`, which is useful for testing code that reads stdin.
+    ///
+    /// The data is written into a pipe by a background thread, so payloads larger than the pipe
+    /// buffer do not block the caller.
+    ///
+    /// # Parameters
+    /// - `data`: The bytes to read from stdin.
+    ///
+    /// # Returns
+    /// - `io::Result<StdinBytesHandle>`: A handle of the writer thread if successful, `Err` otherwise.
+    ///
+    /// # Notes
+    /// Input already buffered by `std::io::stdin()` before the call is still returned first.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use io_redirect::redirect_stdin_from_bytes;
+    ///
+    /// let handle = redirect_stdin_from_bytes(b"hello\n").unwrap();
+    /// let line = std::io::stdin().lines().next().unwrap().unwrap();
+    /// assert_eq!(line, "hello");
+    /// handle.join().unwrap();
+    /// ```
+    pub fn redirect_stdin_from_bytes(data: impl Into<Vec<u8>>) -> io::Result<StdinBytesHandle> {
+        let data = data.into();
+        let (read_end, write_end) = pipes::pipe_cloexec()?;
+        libc_common::redirect_fd_to_fd(STDIN_FILENO, read_end.as_raw_fd())?;
+        drop(read_end);
+
+        let writer = std::thread::spawn(move || {
+            let mut write_end = File::from(write_end);
+            return write_end.write_all(&data);
+        });
+        return Ok(StdinBytesHandle { writer });
+    }
+}
+
+#[cfg(all(unix, feature = "libc_on_unix"))]
+pub use stdin_bytes::*;
+
+#[cfg(all(feature = "std", unix, feature = "libc_on_unix"))]
+mod lock
+{
+    use super::*;
+    use std::os::fd::AsRawFd;
+    use std::path::Path;
+    use libc::{flock, LOCK_EX, LOCK_NB, LOCK_UN};
+
+    /// A redirect to a file that holds an exclusive `flock` lock on it.
+    ///
+    /// Returned by [`redirect_to_path_with_lock`]. Dropping it releases the lock, while the source
+    /// keeps writing to the file.
+    #[derive(Debug)]
+    pub struct LockedRedirect
+    {
+        file: File,
+    }
+
+    impl Drop for LockedRedirect {
+        fn drop(&mut self) {
+            unsafe { flock(self.file.as_raw_fd(), LOCK_UN) };
+        }
+    }
+
+    /// Redirects `source` to the file at `path` while holding an exclusive lock on it, so that
+    /// processes redirecting to the same file through this function do not race each other.
+    ///
+    /// The lock is taken before the file is truncated, so a competing redirect never truncates
+    /// a file that is in use.
+    ///
+    /// # Parameters
+    /// - `source`: The source to redirect.
+    /// - `path`: The path of the file to redirect to, created if it does not exist.
+    /// - `append`: Whether to append to the file instead of truncating it.
+    ///
+    /// # Returns
+    /// - `io::Result<LockedRedirect>`: The lock holder if successful, `Err` otherwise. An error of
+    ///   kind `io::ErrorKind::WouldBlock` means that the file is already locked.
+    ///
+    /// # Notes
+    /// `flock` locks are advisory, so writers that do not take the lock are not kept out.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use io_redirect::redirect_to_path_with_lock;
+    /// use std::path::Path;
+    ///
+    /// let lock = redirect_to_path_with_lock(&mut std::io::stdout(), Path::new("out.log"), false).unwrap();
+    /// println!("only this process writes out.log");
+    /// drop(lock);
+    /// ```
+    pub fn redirect_to_path_with_lock<T: AsRawFd>(source: &mut T, path: &Path, append: bool) -> io::Result<LockedRedirect> {
+        let opts = RedirectOptions { append, truncate: false, ..Default::default() };
+        let file = opts.open(path)?;
+        if unsafe { flock(file.as_raw_fd(), LOCK_EX | LOCK_NB) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let locked = LockedRedirect { file };
+        if !append {
+            locked.file.set_len(0)?;
+        }
+        libc_common::redirect_fd_to_fd(source.as_raw_fd(), locked.file.as_raw_fd())?;
+        return Ok(locked);
+    }
+}
+
+#[cfg(all(feature = "std", unix, feature = "libc_on_unix"))]
+pub use lock::*;
+
+#[cfg(all(unix, feature = "libc_on_unix"))]
+mod syslog
+{
+    use super::*;
+    use std::ffi::{CStr, CString};
+    use libc::{STDERR_FILENO, STDOUT_FILENO};
+
+    /// A syslog facility, mapped to the matching `LOG_*` constant.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub enum SyslogFacility
+    {
+        Daemon,
+        User,
+        Local0,
+        Local1,
+        Local2,
+        Local3,
+        Local4,
+        Local5,
+        Local6,
+        Local7,
+    }
+
+    impl SyslogFacility {
+        fn as_raw(self) -> libc::c_int {
+            return match self {
+                SyslogFacility::Daemon => libc::LOG_DAEMON,
+                SyslogFacility::User => libc::LOG_USER,
+                SyslogFacility::Local0 => libc::LOG_LOCAL0,
+                SyslogFacility::Local1 => libc::LOG_LOCAL1,
+                SyslogFacility::Local2 => libc::LOG_LOCAL2,
+                SyslogFacility::Local3 => libc::LOG_LOCAL3,
+                SyslogFacility::Local4 => libc::LOG_LOCAL4,
+                SyslogFacility::Local5 => libc::LOG_LOCAL5,
+                SyslogFacility::Local6 => libc::LOG_LOCAL6,
+                SyslogFacility::Local7 => libc::LOG_LOCAL7,
+            };
+        }
+    }
+
+    /// Forwards the lines written to stdout and stderr to syslog.
+    ///
+    /// Returned by [`redirect_std_to_syslog`]. Dropping it restores the original destinations,
+    /// waits for the remaining lines to be logged and closes the connection to syslog.
+    #[derive(Debug)]
+    pub struct SyslogRedirectHandle
+    {
+        forwarding: pipes::LineForwarding,
+        _ident: CString,
+    }
+
+    impl Drop for SyslogRedirectHandle {
+        fn drop(&mut self) {
+            self.forwarding.stop();
+            unsafe { libc::closelog() };
+        }
+    }
+
+    /// Redirects the selected standard streams so that each line written to them is sent to syslog.
+    ///
+    /// Lines from stdout are logged with `LOG_INFO` and lines from stderr with `LOG_ERR`. Each
+    /// stream is redirected to its own pipe, drained by a background thread calling `syslog(3)`.
+    ///
+    /// # Parameters
+    /// - `ident`: The identifier prepended to every message, usually the program name.
+    /// - `facility`: The facility to log to.
+    /// - `include_stdout`: Whether to redirect stdout.
+    /// - `include_stderr`: Whether to redirect stderr.
+    ///
+    /// # Returns
+    /// - `io::Result<SyslogRedirectHandle>`: A handle that undoes the redirection on drop if
+    ///   successful, `Err` otherwise.
+    ///
+    /// # Notes
+    /// This calls `openlog(3)`, which affects the whole process. Interior NUL bytes in a line are
+    /// dropped.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use io_redirect::{redirect_std_to_syslog, SyslogFacility};
+    ///
+    /// let handle = redirect_std_to_syslog(c"my-service", SyslogFacility::Daemon, true, true).unwrap();
+    /// println!("appears in syslog");
+    /// drop(handle);
+    /// ```
+    pub fn redirect_std_to_syslog(ident: &CStr, facility: SyslogFacility, include_stdout: bool, include_stderr: bool) -> io::Result<SyslogRedirectHandle> {
+        let ident = ident.to_owned();
+        unsafe { libc::openlog(ident.as_ptr(), libc::LOG_PID, facility.as_raw()) };
+        let mut handle = SyslogRedirectHandle { forwarding: pipes::LineForwarding::default(), _ident: ident };
+
+        let selected = [(include_stdout, STDOUT_FILENO, libc::LOG_INFO), (include_stderr, STDERR_FILENO, libc::LOG_ERR)];
+        for (src_fd, priority) in selected.into_iter().filter(|s| s.0).map(|s| (s.1, s.2)) {
+            handle.forwarding.forward(src_fd, move |mut line| {
+                line.retain(|&b| b != 0);
+                let message = CString::new(line).unwrap_or_default();
+                unsafe { libc::syslog(priority, c"%s".as_ptr(), message.as_ptr()) };
+            })?;
+        }
+        return Ok(handle);
+    }
+}
+
+#[cfg(all(unix, feature = "libc_on_unix"))]
+pub use syslog::*;
+
+#[cfg(all(target_os = "linux", feature = "systemd"))]
+mod journald
+{
+    use super::*;
+    use std::os::fd::{AsRawFd, FromRawFd};
+    use std::os::unix::fs::MetadataExt;
+    use std::os::unix::net::UnixDatagram;
+    use std::path::Path;
+    use std::sync::Arc;
+    use libc::{STDERR_FILENO, STDOUT_FILENO};
+
+    /// The path of the socket accepting native journal protocol messages.
+    pub const JOURNALD_SOCKET: &str = "/run/systemd/journal/socket";
+
+    /// Forwards the lines written to stdout and stderr to the systemd journal.
+    ///
+    /// Returned by [`redirect_std_to_journald`]. Dropping it restores the original destinations
+    /// and waits for the remaining lines to be sent.
+    #[derive(Debug)]
+    pub struct JournaldHandle
+    {
+        forwarding: Option<pipes::LineForwarding>,
+    }
+
+    impl JournaldHandle {
+        /// Returns `true` if the streams were redirected, `false` if they were already connected
+        /// to the journal as announced by `JOURNAL_STREAM`.
+        pub fn is_redirected(&self) -> bool {
+            return self.forwarding.is_some();
+        }
+    }
+
+    /// Redirects stdout and stderr so that each line written to them becomes a journal entry.
+    ///
+    /// Lines from stdout are logged with `PRIORITY=6` (info) and lines from stderr with
+    /// `PRIORITY=3` (error), each sent as a native protocol datagram to [`JOURNALD_SOCKET`].
+    /// If `JOURNAL_STREAM` shows that stdout is already connected to the journal, as it is for
+    /// services started by systemd, nothing is redirected.
+    ///
+    /// # Returns
+    /// - `io::Result<JournaldHandle>`: A handle that undoes the redirection on drop if successful,
+    ///   `Err` with `ErrorKind::NotFound` if the journal socket does not exist, in which case the
+    ///   streams are left untouched and can keep being used as they are, or another `Err` otherwise.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use io_redirect::redirect_std_to_journald;
+    ///
+    /// let handle = redirect_std_to_journald().ok();
+    /// println!("appears in the journal if there is one");
+    /// drop(handle);
+    /// ```
+    pub fn redirect_std_to_journald() -> io::Result<JournaldHandle> {
+        if is_stdout_journal_stream() {
+            return Ok(JournaldHandle { forwarding: None });
+        }
+        return redirect_std_to_journald_socket(JOURNALD_SOCKET);
+    }
+
+    /// Like [`redirect_std_to_journald`], but sends the entries to the socket at `socket_path`
+    /// and ignores `JOURNAL_STREAM`.
+    ///
+    /// This suits journals of containers or other namespaces and tests.
+    pub fn redirect_std_to_journald_socket<P: AsRef<Path>>(socket_path: P) -> io::Result<JournaldHandle> {
+        let socket = UnixDatagram::unbound()?;
+        socket.connect(socket_path)?;
+        let socket = Arc::new(socket);
+
+        let mut forwarding = pipes::LineForwarding::default();
+        for (src_fd, priority) in [(STDOUT_FILENO, 6), (STDERR_FILENO, 3)] {
+            let socket = socket.clone();
+            forwarding.forward(src_fd, move |line| {
+                let mut message = format!("PRIORITY={priority}\nMESSAGE=").into_bytes();
+                message.extend_from_slice(&line);
+                message.push(b'\n');
+                let _ = socket.send(&message);
+            })?;
+        }
+        return Ok(JournaldHandle { forwarding: Some(forwarding) });
+    }
+
+    /// Checks whether stdout is the stream `JOURNAL_STREAM` identifies as `<device>:<inode>`.
+    fn is_stdout_journal_stream() -> bool {
+        let Some(value) = std::env::var_os("JOURNAL_STREAM") else {
+            return false;
+        };
+        let Some((dev, ino)) = value.to_str().and_then(|v| v.split_once(':')) else {
+            return false;
+        };
+        let stdout = std::mem::ManuallyDrop::new(unsafe { File::from_raw_fd(io::stdout().as_raw_fd()) });
+        let Ok(metadata) = stdout.metadata() else {
+            return false;
+        };
+        return dev.parse() == Ok(metadata.dev()) && ino.parse() == Ok(metadata.ino());
+    }
+}
+
+#[cfg(all(target_os = "linux", feature = "systemd"))]
+pub use journald::*;
+
+#[cfg(all(feature = "std", any(all(unix, feature = "libc_on_unix"), all(windows, feature = "windows-sys"))))]
+mod stdin_reader
+{
+    use super::*;
+    use std::io::{Cursor, Read};
+    use std::thread::JoinHandle;
+
+    /// Copies a reader into stdin from a background thread.
+    ///
+    /// Returned by [`redirect_stdin_from_reader`]. Stdin reaches end of file once the reader does.
+    #[derive(Debug)]
+    pub struct StdinReaderHandle
+    {
+        copier: JoinHandle<io::Result<()>>,
+    }
+
+    impl StdinReaderHandle {
+        /// Waits until the reader is exhausted and reports whether reading or copying it failed.
+        pub fn join(self) -> io::Result<()> {
+            return self.copier.join().unwrap_or_else(|_| Err(io::Error::other("stdin copier thread panicked")));
+        }
+    }
+
+    /// Redirects stdin so that it reads whatever `reader` produces.
+    ///
+    /// A pipe is installed as stdin and a background thread copies `reader` into it, so any `Read`
+    /// implementor, such as a `File`, a `Cursor` or a `TcpStream`, can feed stdin.
+    ///
+    /// # Parameters
+    /// - `reader`: The source of the bytes to read from stdin.
+    ///
+    /// # Returns
+    /// - `io::Result<StdinReaderHandle>`: A handle of the copier thread if successful, `Err` otherwise.
+    ///
+    /// # Notes
+    /// Input already buffered by `std::io::stdin()` before the call is still returned first.
+    /// On Windows, the read end of the pipe is leaked as stdin keeps referring to it.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use io_redirect::redirect_stdin_from_reader;
+    ///
+    /// let input = std::fs::File::open("input.txt").unwrap();
+    /// let handle = redirect_stdin_from_reader(input).unwrap();
+    /// let lines: Vec<String> = std::io::stdin().lines().map(Result::unwrap).collect();
+    /// handle.join().unwrap();
+    /// ```
+    pub fn redirect_stdin_from_reader<R: Read + Send + 'static>(mut reader: R) -> io::Result<StdinReaderHandle> {
+        let (read_end, mut write_end) = io::pipe()?;
+        io::stdin().redirect(&read_end)?;
+        if cfg!(not(unix)) {
+            std::mem::forget(read_end);
+        }
+
+        let copier = std::thread::spawn(move || {
+            io::copy(&mut reader, &mut write_end)?;
+            return Ok(());
+        });
+        return Ok(StdinReaderHandle { copier });
+    }
+
+    /// Redirects stdin so that it reads `s`.
+    ///
+    /// This is [`redirect_stdin_from_reader`] over a `Cursor`.
+    pub fn redirect_stdin_from_string(s: impl Into<String>) -> io::Result<StdinReaderHandle> {
+        return redirect_stdin_from_reader(Cursor::new(s.into()));
+    }
+}
+
+#[cfg(all(feature = "std", any(all(unix, feature = "libc_on_unix"), all(windows, feature = "windows-sys"))))]
+pub use stdin_reader::*;
+
+#[cfg(all(unix, feature = "libc_on_unix", feature = "tokio"))]
+mod async_redirect
+{
+    use super::*;
+    use std::future::Future;
+    use std::os::fd::AsRawFd;
+    use std::path::Path;
+
+    /// The async counterpart of [`Redirectable`] for tokio-based callers.
+    ///
+    /// The blocking system calls run on tokio's blocking thread pool, so a tokio runtime must be
+    /// running when the returned future is polled.
+    pub trait AsyncRedirectable<T: ?Sized>
+    {
+        /// Redirects I/O to a specified destination without blocking the async executor.
+        ///
+        /// This is named differently from [`Redirectable::redirect`] so that both traits can be
+        /// in scope at the same time.
+        ///
+        /// # Parameters
+        /// - `destination`: A reference to the target destination.
+        ///
+        /// # Returns
+        /// - `io::Result<()>`: `Ok` if successful, `Err` otherwise.
+        ///
+        /// # Examples
+        /// ```no_run
+        /// use io_redirect::AsyncRedirectable;
+        /// use std::path::Path;
+        ///
+        /// # async fn example() {
+        /// let mut source = std::io::stdout();
+        /// source.redirect_async(Path::new("dst.txt")).await.unwrap();
+        /// # }
+        /// ```
+        fn redirect_async(&mut self, destination: &T) -> impl Future<Output = io::Result<()>> + Send;
+    }
+
+    impl<T1: Descriptable, T2: Descriptable> AsyncRedirectable<T2> for T1 {
+        fn redirect_async(&mut self, destination: &T2) -> impl Future<Output = io::Result<()>> + Send {
+            let src_fd = self.as_raw_fd();
+            let dst_fd = destination.as_raw_fd();
+            return async move { redirect_fd_to_fd_blocking(src_fd, dst_fd).await };
+        }
+    }
+
+    /// Opens the destination with `tokio::fs::OpenOptions`, in append mode and created if it does
+    /// not exist, like `Redirectable<Path>`.
+    impl<T: Descriptable> AsyncRedirectable<Path> for T {
+        fn redirect_async(&mut self, destination: &Path) -> impl Future<Output = io::Result<()>> + Send {
+            let src_fd = self.as_raw_fd();
+            let path = destination.to_path_buf();
+            return async move {
+                let dst = tokio::fs::OpenOptions::new().append(true).create(true).open(&path).await?;
+                return redirect_fd_to_fd_blocking(src_fd, dst.as_raw_fd()).await;
+            };
+        }
+    }
+
+    async fn redirect_fd_to_fd_blocking(src_fd: Descriptor, dst_fd: Descriptor) -> io::Result<()> {
+        return tokio::task::spawn_blocking(move || libc_common::redirect_fd_to_fd(src_fd, dst_fd))
+            .await
+            .unwrap_or_else(|e| Err(io::Error::other(e)));
+    }
+}
+
+#[cfg(all(unix, feature = "libc_on_unix", feature = "tokio"))]
+pub use async_redirect::*;
+
+/// Unix-specific utilities that operate directly on file descriptor numbers.
+#[cfg(all(unix, feature = "libc_on_unix"))]
+pub mod unix
+{
+    use super::*;
+    use std::fs::OpenOptions;
+    use std::mem::ManuallyDrop;
+    use std::os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+    use std::os::unix::fs::MetadataExt;
+    use std::path::{Path, PathBuf};
+    use std::io::{Read, Write};
+    use std::thread::JoinHandle;
+    use libc::{fcntl, F_DUPFD_CLOEXEC, F_GETFD};
+
+    /// Creates an anonymous file backed by memory with `memfd_create(2)`.
+    ///
+    /// The returned `File` can be used as a redirect destination to capture output in memory
+    /// without touching the filesystem. Read it back after seeking to the start.
+    ///
+    /// # Parameters
+    /// - `name`: A name for debugging purposes, shown in `/proc/self/fd`.
+    /// - `flags`: `MFD_*` flags such as `libc::MFD_CLOEXEC` or `libc::MFD_ALLOW_SEALING`.
+    ///
+    /// # Returns
+    /// - `io::Result<File>`: The memory file if successful, `Err` otherwise.
+    #[cfg(all(target_os = "linux", feature = "linux-memfd"))]
+    pub fn create_memfd(name: &std::ffi::CStr, flags: u32) -> io::Result<File> {
+        let fd = unsafe { libc::memfd_create(name.as_ptr(), flags) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        return Ok(unsafe { File::from_raw_fd(fd) });
+    }
+
+    /// Adds `F_SEAL_*` seals to a memory file created with `MFD_ALLOW_SEALING`.
+    ///
+    /// For example, `libc::F_SEAL_WRITE` makes the contents read-only after they have been written.
+    #[cfg(all(target_os = "linux", feature = "linux-memfd"))]
+    pub fn memfd_seal(file: &File, seals: u32) -> io::Result<()> {
+        if unsafe { fcntl(file.as_raw_fd(), libc::F_ADD_SEALS, seals as libc::c_int) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        return Ok(());
+    }
+
+    /// Creates an unnamed regular file in `dir` with `O_TMPFILE`.
+    ///
+    /// The file has no name until it is published with [`link_tmpfile`], so output can be
+    /// redirected to it and only made visible once complete. It is freed when closed if it was
+    /// never linked. Requires Linux 3.11 and a filesystem that supports `O_TMPFILE`.
+    ///
+    /// # Parameters
+    /// - `dir`: The directory whose filesystem holds the file.
+    /// - `mode`: The permission bits used if the file is linked later, such as `0o644`.
+    ///
+    /// # Returns
+    /// - `io::Result<File>`: The unnamed file, opened for reading and writing, if successful, `Err` otherwise.
+    #[cfg(target_os = "linux")]
+    pub fn create_tmpfile(dir: &Path, mode: u32) -> io::Result<File> {
+        use std::os::unix::fs::OpenOptionsExt;
+
+        return OpenOptions::new().read(true).write(true).custom_flags(libc::O_TMPFILE).mode(mode).open(dir);
+    }
+
+    /// Gives a file created with [`create_tmpfile`] the name `name` inside the directory `dest_dir`.
+    ///
+    /// Uses `linkat(AT_EMPTY_PATH)`, which requires `CAP_DAC_READ_SEARCH`. Without that capability
+    /// the file is linked through its `/proc/self/fd` entry instead.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use io_redirect::{unix, Redirectable};
+    /// use std::os::fd::AsRawFd;
+    /// use std::path::Path;
+    ///
+    /// let dir = std::fs::File::open("logs").unwrap();
+    /// let tmpfile = unix::create_tmpfile(Path::new("logs"), 0o644).unwrap();
+    /// std::io::stdout().redirect(&tmpfile).unwrap();
+    /// println!("complete report");
+    /// unix::link_tmpfile(&tmpfile, dir.as_raw_fd(), "report.txt".as_ref()).unwrap();
+    /// ```
+    #[cfg(target_os = "linux")]
+    pub fn link_tmpfile(file: &File, dest_dir: RawFd, name: &std::ffi::OsStr) -> io::Result<()> {
+        use std::os::unix::ffi::OsStrExt;
+
+        let name = std::ffi::CString::new(name.as_bytes()).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        if unsafe { libc::linkat(file.as_raw_fd(), c"".as_ptr(), dest_dir, name.as_ptr(), libc::AT_EMPTY_PATH) } == 0 {
+            return Ok(());
+        }
+        let error = io::Error::last_os_error();
+        if error.raw_os_error() != Some(libc::ENOENT) {
+            return Err(error);
+        }
+
+        return link_proc_fd(file, dest_dir, &name);
+    }
+
+    /// Gives the open file `file` the name `name` inside the directory `dest_dir`.
+    ///
+    /// The file is linked through its `/proc/self/fd` entry with `linkat(AT_SYMLINK_FOLLOW)`, which
+    /// needs no special capability. This materializes a file that only exists as a descriptor, such
+    /// as one created with [`create_tmpfile`] and filled through a redirect, in a single atomic step.
+    ///
+    /// # Parameters
+    /// - `file`: The file to link.
+    /// - `dest_dir`: The directory to create the name in.
+    /// - `name`: The new name, relative to `dest_dir`.
+    ///
+    /// # Returns
+    /// - `io::Result<()>`: `Ok` if successful, `Err` otherwise. Fails with `EXDEV` if `dest_dir` is on
+    ///   another filesystem, which is always the case for files created with `memfd_create`, and with
+    ///   `ENOENT` for unnamed files opened with `O_EXCL`.
+    #[cfg(target_os = "linux")]
+    pub fn link_fd_to_path(file: &File, dest_dir: &File, name: &std::ffi::OsStr) -> io::Result<()> {
+        use std::os::unix::ffi::OsStrExt;
+
+        let name = std::ffi::CString::new(name.as_bytes()).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        return link_proc_fd(file, dest_dir.as_raw_fd(), &name);
+    }
+
+    #[cfg(target_os = "linux")]
+    fn link_proc_fd(file: &File, dest_dir: RawFd, name: &std::ffi::CStr) -> io::Result<()> {
+        let proc_path = std::ffi::CString::new(format!("/proc/self/fd/{}", file.as_raw_fd())).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        if unsafe { libc::linkat(libc::AT_FDCWD, proc_path.as_ptr(), dest_dir, name.as_ptr(), libc::AT_SYMLINK_FOLLOW) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        return Ok(());
+    }
+
+    /// Moves up to `count` bytes from a pipe to another descriptor with `splice(2)`.
+    ///
+    /// The data is moved inside the kernel without being copied to userspace. At least one of
+    /// `src_pipe_read` and `dst_pipe_write` must be a pipe.
+    ///
+    /// # Returns
+    /// - `io::Result<usize>`: The number of bytes moved, `0` at end of file, or `Err` otherwise.
+    #[cfg(all(target_os = "linux", feature = "linux-splice"))]
+    pub fn splice_fd(src_pipe_read: RawFd, dst_pipe_write: RawFd, count: usize) -> io::Result<usize> {
+        let moved = unsafe { libc::splice(src_pipe_read, std::ptr::null_mut(), dst_pipe_write, std::ptr::null_mut(), count, libc::SPLICE_F_MOVE) };
+        if moved < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        return Ok(moved as usize);
+    }
+
+    /// Moves data with [`splice_fd`] until `src` reaches end of file.
+    ///
+    /// # Returns
+    /// - `io::Result<u64>`: The total number of bytes moved if successful, `Err` otherwise.
+    #[cfg(all(target_os = "linux", feature = "linux-splice"))]
+    pub fn splice_loop(src: RawFd, dst: RawFd) -> io::Result<u64> {
+        let mut total = 0u64;
+        loop {
+            match splice_fd(src, dst, 64 * 1024) {
+                Ok(0) => return Ok(total),
+                Ok(moved) => total += moved as u64,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Duplicates the descriptor `remote_fd` of the process `pid` into this process with `pidfd_getfd(2)`.
+    ///
+    /// The caller needs permission to ptrace the target process. The returned descriptor is
+    /// close-on-exec and refers to the same open file as `remote_fd` in the target process.
+    ///
+    /// # Parameters
+    /// - `pid`: The process to borrow from.
+    /// - `remote_fd`: The descriptor number in that process.
+    ///
+    /// # Returns
+    /// - `io::Result<OwnedFd>`: The duplicate if successful, `Err` with `ErrorKind::Unsupported` if
+    ///   the kernel is older than Linux 5.6, or another `Err` otherwise.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use io_redirect::unix::borrow_fd_from_process;
+    ///
+    /// let child = std::process::Command::new("sleep").arg("10").spawn().unwrap();
+    /// let child_stdout = borrow_fd_from_process(child.id(), 1).unwrap();
+    /// ```
+    #[cfg(all(target_os = "linux", feature = "linux-pidfd"))]
+    pub fn borrow_fd_from_process(pid: u32, remote_fd: u32) -> io::Result<OwnedFd> {
+        fn check(result: libc::c_long) -> io::Result<RawFd> {
+            if result < 0 {
+                let error = io::Error::last_os_error();
+                if error.raw_os_error() == Some(libc::ENOSYS) {
+                    return Err(io::Error::new(io::ErrorKind::Unsupported, "pidfd_getfd requires Linux 5.6 or later"));
+                }
+                return Err(error);
+            }
+            return Ok(result as RawFd);
+        }
+
+        let pidfd = check(unsafe { libc::syscall(libc::SYS_pidfd_open, pid as libc::pid_t, 0) })?;
+        let pidfd = unsafe { OwnedFd::from_raw_fd(pidfd) };
+        let fd = check(unsafe { libc::syscall(libc::SYS_pidfd_getfd, pidfd.as_raw_fd(), remote_fd as libc::c_int, 0) })?;
+        return Ok(unsafe { OwnedFd::from_raw_fd(fd) });
+    }
+
+    /// Sends a duplicate of `fd` over a Unix domain socket as `SCM_RIGHTS` ancillary data.
+    ///
+    /// The receiving process gets its own descriptor for the same open file, for example to
+    /// redirect it to a log file on behalf of the sender. A single data byte accompanies the
+    /// descriptor, as some systems do not deliver ancillary data without any payload.
+    ///
+    /// # Parameters
+    /// - `socket`: A connected Unix domain stream socket.
+    /// - `fd`: The descriptor to send. It stays open in this process.
+    ///
+    /// # Returns
+    /// - `io::Result<()>`: `Ok` if successful, `Err` otherwise.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use io_redirect::unix::send_fd;
+    /// use std::os::unix::net::UnixStream;
+    ///
+    /// let socket = UnixStream::connect("/run/log-daemon.sock").unwrap();
+    /// send_fd(&socket, 1).unwrap();
+    /// ```
+    #[cfg(feature = "scm-rights")]
+    pub fn send_fd(socket: &std::os::unix::net::UnixStream, fd: RawFd) -> io::Result<()> {
+        let mut payload = [0u8; 1];
+        let mut iov = libc::iovec { iov_base: payload.as_mut_ptr().cast(), iov_len: payload.len() };
+        let mut control = ControlBuffer { bytes: [0; CONTROL_BUFFER_LEN] };
+
+        let mut msg: libc::msghdr = unsafe { std::mem::zeroed() };
+        msg.msg_iov = &mut iov;
+        msg.msg_iovlen = 1;
+        msg.msg_control = (&mut control as *mut ControlBuffer).cast();
+        msg.msg_controllen = unsafe { libc::CMSG_SPACE(std::mem::size_of::<RawFd>() as u32) } as _;
+        unsafe {
+            let cmsg = libc::CMSG_FIRSTHDR(&msg);
+            (*cmsg).cmsg_level = libc::SOL_SOCKET;
+            (*cmsg).cmsg_type = libc::SCM_RIGHTS;
+            (*cmsg).cmsg_len = libc::CMSG_LEN(std::mem::size_of::<RawFd>() as u32) as _;
+            std::ptr::write_unaligned(libc::CMSG_DATA(cmsg).cast::<RawFd>(), fd);
+        }
+
+        loop {
+            if unsafe { libc::sendmsg(socket.as_raw_fd(), &msg, 0) } >= 0 {
+                return Ok(());
+            }
+            let error = io::Error::last_os_error();
+            if error.kind() != io::ErrorKind::Interrupted {
+                return Err(error);
+            }
+        }
+    }
+
+    /// Receives a descriptor sent with [`send_fd`] over a Unix domain socket.
+    ///
+    /// # Parameters
+    /// - `socket`: A connected Unix domain stream socket.
+    ///
+    /// # Returns
+    /// - `io::Result<OwnedFd>`: The received descriptor, which is close-on-exec, if successful,
+    ///   `Err` with `ErrorKind::UnexpectedEof` if the peer closed the socket, `Err` with
+    ///   `ErrorKind::InvalidData` if the message did not carry exactly one descriptor or its
+    ///   ancillary data was truncated, or another `Err` otherwise. Descriptors received along
+    ///   with an error are closed.
+    #[cfg(feature = "scm-rights")]
+    pub fn recv_fd(socket: &std::os::unix::net::UnixStream) -> io::Result<OwnedFd> {
+        let mut payload = [0u8; 1];
+        let mut iov = libc::iovec { iov_base: payload.as_mut_ptr().cast(), iov_len: payload.len() };
+        let mut control = ControlBuffer { bytes: [0; CONTROL_BUFFER_LEN] };
+
+        let mut msg: libc::msghdr = unsafe { std::mem::zeroed() };
+        msg.msg_iov = &mut iov;
+        msg.msg_iovlen = 1;
+        msg.msg_control = (&mut control as *mut ControlBuffer).cast();
+        msg.msg_controllen = CONTROL_BUFFER_LEN as _;
+
+        #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd"))]
+        let flags = libc::MSG_CMSG_CLOEXEC;
+        #[cfg(not(any(target_os = "linux", target_os = "android", target_os = "freebsd", target_os = "netbsd", target_os = "openbsd")))]
+        let flags = 0;
+        let received = loop {
+            let received = unsafe { libc::recvmsg(socket.as_raw_fd(), &mut msg, flags) };
+            if received >= 0 {
+                break received;
+            }
+            let error = io::Error::last_os_error();
+            if error.kind() != io::ErrorKind::Interrupted {
+                return Err(error);
+            }
+        };
+        if received == 0 {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+        }
+
+        let mut fds = Vec::new();
+        unsafe {
+            let mut cmsg = libc::CMSG_FIRSTHDR(&msg);
+            while !cmsg.is_null() {
+                if (*cmsg).cmsg_level == libc::SOL_SOCKET && (*cmsg).cmsg_type == libc::SCM_RIGHTS {
+                    let data = libc::CMSG_DATA(cmsg);
+                    let count = ((*cmsg).cmsg_len as usize - (data as usize - cmsg as usize)) / std::mem::size_of::<RawFd>();
+                    for i in 0..count {
+                        fds.push(OwnedFd::from_raw_fd(std::ptr::read_unaligned(data.cast::<RawFd>().add(i))));
+                    }
+                }
+                cmsg = libc::CMSG_NXTHDR(&msg, cmsg);
+            }
+        }
+        if msg.msg_flags & libc::MSG_CTRUNC != 0 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "ancillary data was truncated"));
+        }
+        if fds.len() != 1 {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, format!("message carries {} file descriptors instead of one", fds.len())));
+        }
+        let fd = fds.remove(0);
+        if flags == 0 && unsafe { fcntl(fd.as_raw_fd(), libc::F_SETFD, libc::FD_CLOEXEC) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        return Ok(fd);
+    }
+
+    /// Room for the ancillary data of a few descriptors, so that extra ones sent by a peer are
+    /// received and closed rather than truncated.
+    #[cfg(feature = "scm-rights")]
+    const CONTROL_BUFFER_LEN: usize = 128;
+
+    /// A control message buffer aligned for `cmsghdr`, as `CMSG_FIRSTHDR` and `CMSG_NXTHDR` require.
+    #[cfg(feature = "scm-rights")]
+    #[repr(C)]
+    union ControlBuffer
+    {
+        _header: libc::cmsghdr,
+        bytes: [u8; CONTROL_BUFFER_LEN],
+    }
+
+    /// Restores a hijacked file descriptor number to its original target when dropped.
+    ///
+    /// Returned by [`hijack_fd_number`].
+    #[derive(Debug)]
+    pub struct HijackGuard
+    {
+        fd_number: RawFd,
+        original: OwnedFd,
+    }
+
+    impl Drop for HijackGuard {
+        fn drop(&mut self) {
+            let _ = libc_common::redirect_fd_to_fd(self.fd_number, self.original.as_raw_fd());
+        }
+    }
+
+    /// Overwrites the descriptor slot `fd_number` so that it refers to the same file as `new_dest`.
+    ///
+    /// Unlike [`Redirectable`], the caller names the slot to take over rather than an object that
+    /// owns it. Whoever holds `fd_number` keeps using the same number without noticing the change.
+    /// The original target is saved with [`dup_cloexec`], so it never leaks into child processes,
+    /// and is put back when the returned guard is dropped.
+    ///
+    /// # Parameters
+    /// - `fd_number`: The descriptor number to overwrite.
+    /// - `new_dest`: A descriptor referring to the new target.
+    ///
+    /// # Returns
+    /// - `io::Result<HijackGuard>`: A guard restoring `fd_number` on drop if successful, `Err` otherwise.
+    ///
+    /// # Notes
+    /// This is inherently racy in multithreaded programs. Only use it when no other thread is
+    /// concurrently reading, writing, opening or closing `fd_number`.
+    pub fn hijack_fd_number(fd_number: RawFd, new_dest: RawFd) -> io::Result<HijackGuard> {
+        let original = dup_cloexec(fd_number)?;
+
+        libc_common::redirect_fd_to_fd(fd_number, new_dest)?;
+
+        return Ok(HijackGuard { fd_number, original });
+    }
+
+    /// Lists the file descriptors currently open in this process.
+    ///
+    /// On Linux this enumerates `/proc/self/fd`, elsewhere `/dev/fd`. The descriptor used for the
+    /// enumeration itself is excluded from the result.
+    pub fn list_open_fds() -> io::Result<Vec<RawFd>> {
+        let fd_dir = if cfg!(target_os = "linux") { "/proc/self/fd" } else { "/dev/fd" };
+        let mut fds = Vec::new();
+        for entry in std::fs::read_dir(fd_dir)? {
+            if let Some(fd) = entry?.file_name().to_str().and_then(|name| name.parse::<RawFd>().ok()) {
+                fds.push(fd);
+            }
+        }
+        fds.retain(|&fd| unsafe { fcntl(fd, F_GETFD) } >= 0);
+        return Ok(fds);
+    }
+
+    /// Points every open descriptor referring to `old_path` at `new_path` instead.
+    ///
+    /// Descriptors are matched by device and inode number, so it does not matter which subsystem
+    /// opened them or under which name. `new_path` is opened (and created if missing) in append mode
+    /// and each matching descriptor is overwritten with `dup2`. This is intended for log rotation
+    /// where all references to the rotated file need to move to the fresh one.
+    ///
+    /// # Parameters
+    /// - `old_path`: The file whose open descriptors should be migrated.
+    /// - `new_path`: The file those descriptors should refer to afterwards.
+    ///
+    /// # Returns
+    /// - `io::Result<usize>`: The number of migrated descriptors if successful, `Err` otherwise.
+    ///
+    /// # Notes
+    /// Requires the old and new files to be on the same filesystem. Descriptors migrated before a
+    /// failing `dup2` stay migrated.
+    pub fn migrate_redirect(old_path: &Path, new_path: &Path) -> io::Result<usize> {
+        let old_metadata = std::fs::metadata(old_path)?;
+        let new_file = OpenOptions::new().create(true).append(true).open(new_path)?;
+
+        let mut migrated = 0;
+        for fd in list_open_fds()? {
+            if fd == new_file.as_raw_fd() {
+                continue;
+            }
+            let file = ManuallyDrop::new(unsafe { File::from_raw_fd(fd) });
+            let Ok(metadata) = file.metadata() else {
+                continue;
+            };
+            if metadata.dev() == old_metadata.dev() && metadata.ino() == old_metadata.ino() {
+                libc_common::redirect_fd_to_fd(fd, new_file.as_raw_fd())?;
+                migrated += 1;
+            }
+        }
+
+        return Ok(migrated);
+    }
+
+    /// A descriptor redirected to a file by [`redirect_to_path`], which can be pointed at a new file later.
+    ///
+    /// The handle keeps the opened destination alongside the number of the redirected descriptor,
+    /// so that [`reopen_redirect`] can swap the destination without the owner of the descriptor
+    /// noticing.
+    #[derive(Debug)]
+    pub struct RedirectHandle
+    {
+        src_fd: RawFd,
+        destination: File,
+    }
+
+    impl RedirectHandle {
+        /// Returns the number of the redirected descriptor.
+        pub fn src_fd(&self) -> RawFd {
+            return self.src_fd;
+        }
+
+        /// Returns the file the descriptor currently refers to.
+        pub fn destination(&self) -> &File {
+            return &self.destination;
+        }
+    }
+
+    /// Redirects `source` to the file at `path`, opened according to `opts`, and returns a handle
+    /// for reopening it later.
+    ///
+    /// # Parameters
+    /// - `source`: The entity to redirect.
+    /// - `path`: The path of the file to redirect to.
+    /// - `opts`: How to open the file.
+    ///
+    /// # Returns
+    /// - `io::Result<RedirectHandle>`: A handle for [`reopen_redirect`] if successful, `Err` otherwise.
+    pub fn redirect_to_path<T: AsRawFd, P: AsRef<Path>>(source: &mut T, path: P, opts: &RedirectOptions) -> io::Result<RedirectHandle> {
+        let destination = opts.open(path.as_ref())?;
+        libc_common::redirect_fd_to_fd(source.as_raw_fd(), destination.as_raw_fd())?;
+        return Ok(RedirectHandle { src_fd: source.as_raw_fd(), destination });
+    }
+
+    /// Points the descriptor redirected by `handle` at a freshly opened `new_path`.
+    ///
+    /// This is meant for log rotation, e.g. on `SIGHUP` after `logrotate` renamed the file. The
+    /// descriptor number stays the same, so writers do not need to stop.
+    ///
+    /// # Parameters
+    /// - `handle`: The redirection to update.
+    /// - `new_path`: The path of the file to redirect to, usually the original path.
+    /// - `opts`: How to open the file.
+    ///
+    /// # Returns
+    /// - `io::Result<()>`: `Ok` if successful, `Err` otherwise, in which case the descriptor keeps
+    ///   referring to the previous file.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use io_redirect::RedirectOptions;
+    /// use io_redirect::unix::{redirect_to_path, reopen_redirect};
+    /// use std::path::Path;
+    ///
+    /// let log = Path::new("/var/log/app.log");
+    /// let mut handle = redirect_to_path(&mut std::io::stdout(), log, &RedirectOptions::default()).unwrap();
+    /// // after logrotate renamed /var/log/app.log
+    /// reopen_redirect(&mut handle, log, &RedirectOptions::default()).unwrap();
+    /// ```
+    pub fn reopen_redirect(handle: &mut RedirectHandle, new_path: &Path, opts: &RedirectOptions) -> io::Result<()> {
+        let destination = opts.open(new_path)?;
+        libc_common::redirect_fd_to_fd(handle.src_fd, destination.as_raw_fd())?;
+        handle.destination = destination;
+        return Ok(());
+    }
+
+    /// A pool of descriptors opened ahead of time on the same file.
+    ///
+    /// Acquiring a descriptor from the pool avoids an `open` call on the hot path, which helps with
+    /// high-frequency redirections such as switching log files per request in a server.
+    #[derive(Debug)]
+    pub struct FdPool
+    {
+        fds: Vec<OwnedFd>,
+        source: PathBuf,
+        capacity: usize,
+    }
+
+    impl FdPool {
+        /// Opens `path` `count` times in append mode, creating it if missing.
+        ///
+        /// # Parameters
+        /// - `path`: The file to preallocate descriptors for.
+        /// - `count`: The number of descriptors to keep in the pool.
+        ///
+        /// # Returns
+        /// - `io::Result<FdPool>`: The filled pool if successful, `Err` otherwise.
+        pub fn preallocate(path: &Path, count: usize) -> io::Result<FdPool> {
+            let mut pool = FdPool { fds: Vec::with_capacity(count), source: path.to_path_buf(), capacity: count };
+            pool.refill()?;
+            return Ok(pool);
+        }
+
+        /// Takes a preallocated descriptor out of the pool, or `None` if the pool is exhausted.
+        pub fn acquire(&mut self) -> Option<OwnedFd> {
+            return self.fds.pop();
+        }
+
+        /// Opens new descriptors until the pool holds as many as it was preallocated with.
+        pub fn refill(&mut self) -> io::Result<()> {
+            while self.fds.len() < self.capacity {
+                let file = OpenOptions::new().create(true).append(true).open(&self.source)?;
+                self.fds.push(OwnedFd::from(file));
+            }
+            return Ok(());
+        }
+    }
+
+    /// Forwards everything readable from one descriptor to another.
+    ///
+    /// This is typically used with a relay pipe whose read end must be drained into an arbitrary
+    /// destination descriptor.
+    #[derive(Debug)]
+    pub struct FdProxy
+    {
+        read_fd: OwnedFd,
+        write_fd: OwnedFd,
+        buf_size: usize,
+    }
+
+    impl FdProxy {
+        /// Creates a proxy copying from `read_fd` to `write_fd` with an 8 KiB buffer.
+        pub fn new(read_fd: OwnedFd, write_fd: OwnedFd) -> FdProxy {
+            return FdProxy { read_fd, write_fd, buf_size: 8 * 1024 };
+        }
+
+        /// Sets the size of the buffer used for each read.
+        pub fn with_buf_size(mut self, buf_size: usize) -> FdProxy {
+            self.buf_size = buf_size.max(1);
+            return self;
+        }
+
+        /// Copies data until `read_fd` reaches end of file, blocking the calling thread.
+        ///
+        /// # Returns
+        /// - `io::Result<u64>`: The number of forwarded bytes if successful, `Err` otherwise.
+        pub fn run(self) -> io::Result<u64> {
+            let mut reader = File::from(self.read_fd);
+            let mut writer = File::from(self.write_fd);
+            let mut buf = vec![0u8; self.buf_size];
+            let mut forwarded = 0u64;
+            loop {
+                let count = match reader.read(&mut buf) {
+                    Ok(0) => return Ok(forwarded),
+                    Ok(count) => count,
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(e) => return Err(e),
+                };
+                writer.write_all(&buf[..count])?;
+                forwarded += count as u64;
+            }
+        }
+
+        /// Runs [`FdProxy::run`] on a background thread.
+        pub fn run_async(self) -> JoinHandle<io::Result<u64>> {
+            return std::thread::spawn(move || self.run());
+        }
+    }
+
+    /// A file descriptor number, as opposed to an object owning a descriptor.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub struct FdNumber(pub RawFd);
+
+    /// Applies `(from, to)` pairs in the given order, making each `to` refer to the file of `from`.
+    ///
+    /// Pairs are applied one after another with `dup2`, so a pair reading from a descriptor that an
+    /// earlier pair overwrote observes the new target. Use [`FdRemap`] when all pairs must read the
+    /// original targets.
+    pub fn dup2_range(pairs: &[(FdNumber, FdNumber)]) -> io::Result<()> {
+        for &(from, to) in pairs {
+            libc_common::redirect_fd_to_fd(to.0, from.0)?;
+        }
+        return Ok(());
+    }
+
+    /// Error returned by [`FdRemap::validate`] when several pairs target the same descriptor.
+    #[derive(Debug)]
+    pub struct FdRemapError
+    {
+        /// Every pair whose target is also the target of a pair with a different source.
+        pub conflicts: Vec<(FdNumber, FdNumber)>,
+    }
+
+    impl std::fmt::Display for FdRemapError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "conflicting fd remap pairs:")?;
+            for (from, to) in &self.conflicts {
+                write!(f, " {} -> {}", from.0, to.0)?;
+            }
+            return Ok(());
+        }
+    }
+
+    impl std::error::Error for FdRemapError {}
+
+    /// A set of descriptor renumberings applied as if all of them happened at once.
+    ///
+    /// This is what is needed to lay out descriptors before `exec`: each target ends up referring
+    /// to the file its source referred to before [`FdRemap::apply`] was called, even when targets
+    /// are also sources of other pairs.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use io_redirect::unix::{FdNumber, FdRemap};
+    ///
+    /// // swap stdout and stderr
+    /// FdRemap::new()
+    ///     .add(FdNumber(1), FdNumber(2))
+    ///     .add(FdNumber(2), FdNumber(1))
+    ///     .apply()
+    ///     .unwrap();
+    /// ```
+    #[derive(Clone, Debug, Default)]
+    pub struct FdRemap(Vec<(FdNumber, FdNumber)>);
+
+    impl FdRemap {
+        /// Creates an empty remap.
+        pub fn new() -> FdRemap {
+            return FdRemap::default();
+        }
+
+        /// Adds a pair making `to` refer to the file `from` currently refers to.
+        pub fn add(mut self, from: FdNumber, to: FdNumber) -> FdRemap {
+            self.0.push((from, to));
+            return self;
+        }
+
+        /// Checks that no descriptor is the target of pairs with different sources.
+        pub fn validate(&self) -> Result<(), FdRemapError> {
+            let conflicts: Vec<_> = self.0.iter()
+                .filter(|&&(from, to)| self.0.iter().any(|&(other_from, other_to)| other_to == to && other_from != from))
+                .copied()
+                .collect();
+            if !conflicts.is_empty() {
+                return Err(FdRemapError { conflicts });
+            }
+            return Ok(());
+        }
+
+        /// Validates and applies all pairs.
+        ///
+        /// Pairs are ordered so that no descriptor is overwritten while another pair still needs
+        /// to read it. Cycles are broken by saving one source to a temporary descriptor numbered
+        /// above every descriptor in the remap, which is closed once all pairs are applied.
+        ///
+        /// # Returns
+        /// - `io::Result<()>`: `Ok` if successful, `Err` with `ErrorKind::InvalidInput` if
+        ///   validation fails, or the OS error of the failing call otherwise.
+        ///
+        /// # Notes
+        /// If a `dup2` call fails, the pairs applied before it stay applied.
+        pub fn apply(self) -> io::Result<()> {
+            self.validate().map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+            let lowest_temporary = self.0.iter().map(|&(from, to)| from.0.max(to.0)).max().unwrap_or(0) + 1;
+            let mut pending: Vec<_> = self.0.into_iter().filter(|&(from, to)| from != to).collect();
+            let mut ordered = Vec::with_capacity(pending.len());
+            let mut temporaries = Vec::new();
+            while !pending.is_empty() {
+                let ready = pending.iter().position(|&(_, to)| !pending.iter().any(|&(other_from, _)| other_from == to));
+                match ready {
+                    Some(index) => ordered.push(pending.remove(index)),
+                    None => {
+                        let (from, _) = pending[0];
+                        let temporary = unsafe { fcntl(from.0, F_DUPFD_CLOEXEC, lowest_temporary) };
+                        if temporary < 0 {
+                            return Err(io::Error::last_os_error());
+                        }
+                        let temporary = unsafe { OwnedFd::from_raw_fd(temporary) };
+                        for pair in pending.iter_mut().filter(|pair| pair.0 == from) {
+                            pair.0 = FdNumber(temporary.as_raw_fd());
+                        }
+                        temporaries.push(temporary);
+                    }
+                }
+            }
+
+            return dup2_range(&ordered);
+        }
+    }
+
+    /// Makes `new_src_fd` share the redirect target of `existing_fd`, like `2>&1` after `>log.txt`.
+    ///
+    /// Both descriptors end up referring to the same open file description and hence share one
+    /// file offset, so interleaved writes through either of them keep their order. Opening the
+    /// file again, as `redirect_std_pair` does for its two paths, creates independent offsets instead.
+    ///
+    /// # Parameters
+    /// - `existing_fd`: The already redirected descriptor, such as `libc::STDOUT_FILENO`.
+    /// - `new_src_fd`: The descriptor to redirect to the same target, such as `libc::STDERR_FILENO`.
+    ///
+    /// # Returns
+    /// - `io::Result<()>`: `Ok` if successful, `Err` otherwise.
+    pub fn extend_redirect(existing_fd: RawFd, new_src_fd: RawFd) -> io::Result<()> {
+        return libc_common::redirect_fd_to_fd(new_src_fd, existing_fd);
+    }
+
+    /// Swaps the redirect targets of two descriptors, e.g. to exchange the files stdout and stderr
+    /// write to.
+    ///
+    /// The target of `fd_a` is saved to a temporary close-on-exec descriptor, `fd_a` is redirected
+    /// to the target of `fd_b`, and `fd_b` to the saved target. Swapping a descriptor with itself
+    /// does nothing.
+    ///
+    /// # Parameters
+    /// - `fd_a`: The first descriptor, such as `libc::STDOUT_FILENO`.
+    /// - `fd_b`: The second descriptor, such as `libc::STDERR_FILENO`.
+    ///
+    /// # Returns
+    /// - `io::Result<()>`: `Ok` if successful, `Err` otherwise. If the second redirect fails,
+    ///   `fd_a` is restored to its original target on a best-effort basis.
+    ///
+    /// # Notes
+    /// The swap is not atomic for other threads, which may briefly see both descriptors referring
+    /// to the target of `fd_b`.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use io_redirect::unix::redirect_pair_swap;
+    ///
+    /// redirect_pair_swap(libc::STDOUT_FILENO, libc::STDERR_FILENO).unwrap();
+    /// ```
+    pub fn redirect_pair_swap(fd_a: RawFd, fd_b: RawFd) -> io::Result<()> {
+        if fd_a == fd_b {
+            return Ok(());
+        }
+        let saved_a = dup_cloexec(fd_a)?;
+        libc_common::redirect_fd_to_fd(fd_a, fd_b)?;
+        if let Err(error) = libc_common::redirect_fd_to_fd(fd_b, saved_a.as_raw_fd()) {
+            let _ = libc_common::redirect_fd_to_fd(fd_a, saved_a.as_raw_fd());
+            return Err(error);
+        }
+        return Ok(());
+    }
+
+    /// Applies `redirects` and replaces the current process image with `executable`.
+    ///
+    /// This is the "set up stdio, then exec" step of shell-like tools. Each `(src, dst)` pair is
+    /// applied in order with `dup2(dst, src)`, which also clears close-on-exec on `src` so that the
+    /// new program inherits it. The new program is then started with `execv`, inheriting the current
+    /// environment, or with `execve` and exactly `env` if `env` is not empty.
+    ///
+    /// # Parameters
+    /// - `executable`: The path of the program to run. It is also passed as `argv[0]`.
+    /// - `args`: The remaining arguments, starting at `argv[1]`.
+    /// - `env`: The environment of the new program, or empty to inherit the current one.
+    /// - `redirects`: `(src, dst)` pairs of descriptors, applied in order.
+    ///
+    /// # Returns
+    /// - `io::Error`: Only returns on failure. Redirections applied before a failure are not undone.
+    ///   Arguments, variables or paths containing a NUL byte yield `ErrorKind::InvalidInput`
+    ///   before anything is redirected.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use io_redirect::unix::redirect_and_exec;
+    /// use std::os::fd::AsRawFd;
+    /// use std::path::Path;
+    ///
+    /// let log = std::fs::File::create("echo.log").unwrap();
+    /// let error = redirect_and_exec(Path::new("/bin/echo"), &["hello".into()], &[], &[(libc::STDOUT_FILENO, log.as_raw_fd())]);
+    /// panic!("exec failed: {error}");
+    /// ```
+    pub fn redirect_and_exec(executable: &Path, args: &[std::ffi::OsString], env: &[(std::ffi::OsString, std::ffi::OsString)], redirects: &[(RawFd, RawFd)]) -> io::Error {
+        use std::ffi::{CString, OsStr};
+        use std::os::unix::ffi::OsStrExt;
+
+        fn to_cstring(value: &OsStr) -> io::Result<CString> {
+            return CString::new(value.as_bytes()).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e));
+        }
+
+        let prepared = (|| -> io::Result<(CString, Vec<CString>, Vec<CString>)> {
+            let program = to_cstring(executable.as_os_str())?;
+            let mut argv = vec![program.clone()];
+            for arg in args {
+                argv.push(to_cstring(arg)?);
+            }
+            let mut envp = Vec::with_capacity(env.len());
+            for (key, value) in env {
+                let mut pair = key.clone();
+                pair.push("=");
+                pair.push(value);
+                envp.push(to_cstring(&pair)?);
+            }
+            return Ok((program, argv, envp));
+        })();
+        let (program, argv, envp) = match prepared {
+            Ok(prepared) => prepared,
+            Err(e) => return e,
+        };
+        let argv_ptrs: Vec<*const libc::c_char> = argv.iter().map(|arg| arg.as_ptr()).chain(std::iter::once(std::ptr::null())).collect();
+        let envp_ptrs: Vec<*const libc::c_char> = envp.iter().map(|pair| pair.as_ptr()).chain(std::iter::once(std::ptr::null())).collect();
+
+        for &(src, dst) in redirects {
+            if let Err(e) = libc_common::redirect_fd_to_fd(src, dst) {
+                return e;
+            }
+        }
+
+        if envp.is_empty() {
+            unsafe { libc::execv(program.as_ptr(), argv_ptrs.as_ptr()) };
+        } else {
+            unsafe { libc::execve(program.as_ptr(), argv_ptrs.as_ptr(), envp_ptrs.as_ptr()) };
+        }
+        return io::Error::last_os_error();
+    }
+
+    /// File actions applied in a child started with [`spawn_with_actions`], before the new program runs.
+    ///
+    /// This wraps `posix_spawn_file_actions_t`. Setting up the standard streams of a child this way
+    /// needs no `fork()`, so it is safe in multithreaded programs and cheap for large processes.
+    /// Actions are applied in the order they are added.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use io_redirect::unix::{spawn_with_actions, PosixSpawnActions};
+    /// use std::path::Path;
+    ///
+    /// let mut actions = PosixSpawnActions::new().unwrap();
+    /// actions.add_open(libc::STDOUT_FILENO, Path::new("ls.log"), libc::O_WRONLY | libc::O_CREAT | libc::O_TRUNC, 0o644).unwrap();
+    /// actions.add_dup2(libc::STDOUT_FILENO, libc::STDERR_FILENO).unwrap();
+    /// let pid = spawn_with_actions(Path::new("/bin/ls"), &["-l".as_ref()], &actions).unwrap();
+    /// ```
+    pub struct PosixSpawnActions(libc::posix_spawn_file_actions_t);
+
+    impl PosixSpawnActions {
+        /// Creates an empty set of file actions.
+        pub fn new() -> io::Result<PosixSpawnActions> {
+            let mut actions = std::mem::MaybeUninit::<libc::posix_spawn_file_actions_t>::uninit();
+            check_spawn_result(unsafe { libc::posix_spawn_file_actions_init(actions.as_mut_ptr()) })?;
+            return Ok(PosixSpawnActions(unsafe { actions.assume_init() }));
+        }
+
+        /// Makes `dst` refer to the file of `src` in the child, like `dup2(src, dst)`.
+        ///
+        /// Close-on-exec is cleared on `dst`, so `src` may be a close-on-exec descriptor of this process.
+        pub fn add_dup2(&mut self, src: RawFd, dst: RawFd) -> io::Result<()> {
+            return check_spawn_result(unsafe { libc::posix_spawn_file_actions_adddup2(&mut self.0, src, dst) });
+        }
+
+        /// Closes `fd` in the child.
+        pub fn add_close(&mut self, fd: RawFd) -> io::Result<()> {
+            return check_spawn_result(unsafe { libc::posix_spawn_file_actions_addclose(&mut self.0, fd) });
+        }
+
+        /// Opens `path` as `fd` in the child, like `open(path, flags, mode)` followed by `dup2`.
+        ///
+        /// # Returns
+        /// - `io::Result<()>`: `Ok` if successful, `Err` otherwise. Failing to open `path` is only
+        ///   detected when spawning.
+        pub fn add_open(&mut self, fd: RawFd, path: &Path, flags: i32, mode: u32) -> io::Result<()> {
+            use std::os::unix::ffi::OsStrExt;
+
+            let path = std::ffi::CString::new(path.as_os_str().as_bytes()).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+            return check_spawn_result(unsafe { libc::posix_spawn_file_actions_addopen(&mut self.0, fd, path.as_ptr(), flags, mode as libc::mode_t) });
+        }
+    }
+
+    impl Drop for PosixSpawnActions {
+        fn drop(&mut self) {
+            unsafe { libc::posix_spawn_file_actions_destroy(&mut self.0) };
+        }
+    }
+
+    impl std::fmt::Debug for PosixSpawnActions {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            return f.debug_struct("PosixSpawnActions").finish_non_exhaustive();
+        }
+    }
+
+    /// Starts `exe` with `posix_spawn`, applying `actions` in the child.
+    ///
+    /// The child inherits the environment of this process. `exe` is passed as `argv[0]`, followed by `args`.
+    ///
+    /// # Returns
+    /// - `io::Result<u32>`: The process ID of the child if successful, `Err` otherwise. The caller
+    ///   is responsible for waiting for the child, e.g. with `libc::waitpid`.
+    pub fn spawn_with_actions(exe: &Path, args: &[&std::ffi::OsStr], actions: &PosixSpawnActions) -> io::Result<u32> {
+        use std::ffi::CString;
+        use std::os::unix::ffi::OsStrExt;
+
+        let to_cstring = |value: &[u8]| CString::new(value).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e));
+        let program = to_cstring(exe.as_os_str().as_bytes())?;
+        let mut argv = vec![program.clone()];
+        for arg in args {
+            argv.push(to_cstring(arg.as_bytes())?);
+        }
+        let mut envp = Vec::new();
+        for (key, value) in std::env::vars_os() {
+            envp.push(to_cstring(&[key.as_bytes(), b"=", value.as_bytes()].concat())?);
+        }
+        let mut argv_ptrs: Vec<*mut libc::c_char> = argv.iter().map(|arg| arg.as_ptr().cast_mut()).collect();
+        argv_ptrs.push(std::ptr::null_mut());
+        let mut envp_ptrs: Vec<*mut libc::c_char> = envp.iter().map(|pair| pair.as_ptr().cast_mut()).collect();
+        envp_ptrs.push(std::ptr::null_mut());
+
+        let mut pid: libc::pid_t = 0;
+        check_spawn_result(unsafe { libc::posix_spawn(&mut pid, program.as_ptr(), &actions.0, std::ptr::null(), argv_ptrs.as_ptr(), envp_ptrs.as_ptr()) })?;
+        return Ok(pid as u32);
+    }
+
+    /// Converts the error number returned by the `posix_spawn` family, which does not set `errno`.
+    fn check_spawn_result(result: libc::c_int) -> io::Result<()> {
+        if result != 0 {
+            return Err(io::Error::from_raw_os_error(result));
+        }
+        return Ok(());
+    }
+
+    /// Redirects every descriptor number from `start` to `end` inclusive to the file of `dest`.
+    ///
+    /// Numbers in the range that are not open yet are opened as a result. `dest` itself is
+    /// skipped if it is in the range.
+    ///
+    /// # Returns
+    /// - `io::Result<()>`: `Ok` if successful, `Err` with `ErrorKind::InvalidInput` if the range
+    ///   is empty or negative, or the OS error of the failing call otherwise.
+    ///
+    /// # Notes
+    /// The function stops at the first failing descriptor. The descriptors before it stay
+    /// redirected and the ones after it are left untouched.
+    pub fn redirect_fd_range(start: RawFd, end: RawFd, dest: RawFd) -> io::Result<()> {
+        validate_fd_range(start, end)?;
+        for fd in (start..=end).filter(|&fd| fd != dest) {
+            libc_common::redirect_fd_to_fd(fd, dest)?;
+        }
+        return Ok(());
+    }
+
+    /// Closes every descriptor number from `start` to `end` inclusive.
+    ///
+    /// Uses `close_range(2)` on Linux 5.9 and later, and closes the descriptors one by one
+    /// otherwise. Numbers in the range that are not open are skipped.
+    ///
+    /// # Returns
+    /// - `io::Result<()>`: `Ok` if successful, `Err` with `ErrorKind::InvalidInput` if the range
+    ///   is empty or negative, or the OS error of the failing call otherwise.
+    pub fn close_fd_range(start: RawFd, end: RawFd) -> io::Result<()> {
+        validate_fd_range(start, end)?;
+        #[cfg(target_os = "linux")]
+        if unsafe { libc::syscall(libc::SYS_close_range, start as libc::c_uint, end as libc::c_uint, 0 as libc::c_uint) } == 0 {
+            return Ok(());
+        }
+        for fd in start..=end {
+            close_ignoring_ebadf(fd)?;
+        }
+        return Ok(());
+    }
+
+    /// Closes every descriptor above `stderr`, as daemons do right after `fork()`.
+    ///
+    /// Uses `close_range(2)` on Linux 5.9 and later. Older Linux kernels enumerate `/proc/self/fd`
+    /// so that only descriptors that are actually open get closed. Other systems close every number
+    /// from 3 up to `sysconf(_SC_OPEN_MAX)`, skipping the ones that are not open.
+    ///
+    /// # Returns
+    /// - `io::Result<()>`: `Ok` if successful, the OS error of the failing call otherwise.
+    ///
+    /// # Notes
+    /// This closes descriptors owned by other parts of the process as well, including other threads.
+    /// It is only safe to call while the process is single-threaded.
+    pub fn close_inherited_fds() -> io::Result<()> {
+        #[cfg(target_os = "linux")]
+        {
+            if unsafe { libc::syscall(libc::SYS_close_range, 3 as libc::c_uint, libc::c_uint::MAX, 0 as libc::c_uint) } == 0 {
+                return Ok(());
+            }
+            for fd in list_open_fds()?.into_iter().filter(|&fd| fd > libc::STDERR_FILENO) {
+                close_ignoring_ebadf(fd)?;
+            }
+            return Ok(());
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            let open_max = unsafe { libc::sysconf(libc::_SC_OPEN_MAX) };
+            let open_max = if open_max < 0 { 1024 } else { open_max.min(RawFd::MAX as libc::c_long) as RawFd };
+            for fd in (libc::STDERR_FILENO + 1)..open_max {
+                close_ignoring_ebadf(fd)?;
+            }
+            return Ok(());
+        }
+    }
+
+    fn close_ignoring_ebadf(fd: RawFd) -> io::Result<()> {
+        if unsafe { libc::close(fd) } < 0 {
+            let error = io::Error::last_os_error();
+            if error.raw_os_error() != Some(libc::EBADF) {
+                return Err(error);
+            }
+        }
+        return Ok(());
+    }
+
+    fn validate_fd_range(start: RawFd, end: RawFd) -> io::Result<()> {
+        if start < 0 || start > end {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, format!("invalid descriptor range {start}..={end}")));
+        }
+        return Ok(());
+    }
+
+    /// Kernel facilities available to this process, as detected by [`detect_platform_capabilities`].
+    ///
+    /// Facilities that this crate only uses on Linux are reported as unavailable elsewhere.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct PlatformCapabilities
+    {
+        /// `/proc/self/fd` lists the open descriptors of this process.
+        pub has_proc_fd: bool,
+        /// `fcntl(F_DUPFD_CLOEXEC)` duplicates descriptors with close-on-exec set atomically.
+        pub has_cloexec_dup: bool,
+        /// `memfd_create(2)` creates anonymous memory files (Linux 3.17).
+        pub has_memfd_create: bool,
+        /// `splice(2)` moves data between descriptors inside the kernel.
+        pub has_splice: bool,
+        /// `close_range(2)` closes descriptor ranges in one call (Linux 5.9).
+        pub has_close_range: bool,
+        /// `pidfd_open(2)` opens process descriptors (Linux 5.3).
+        pub has_pidfd: bool,
+    }
+
+    /// Detects which kernel facilities are available at runtime.
+    ///
+    /// Which of them work depends on the running kernel and on sandboxing such as seccomp filters,
+    /// not only on the target the crate was compiled for. Each facility is probed with arguments
+    /// that have no lasting side effects, and the results are cached for the lifetime of the process.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use io_redirect::unix::detect_platform_capabilities;
+    ///
+    /// if !detect_platform_capabilities().has_close_range {
+    ///     eprintln!("closing descriptors one by one");
+    /// }
+    /// ```
+    pub fn detect_platform_capabilities() -> PlatformCapabilities {
+        static CAPABILITIES: std::sync::OnceLock<PlatformCapabilities> = std::sync::OnceLock::new();
+        return *CAPABILITIES.get_or_init(probe_platform_capabilities);
+    }
+
+    fn probe_platform_capabilities() -> PlatformCapabilities {
+        #[cfg_attr(not(target_os = "linux"), allow(unused_mut))]
+        let mut capabilities = PlatformCapabilities {
+            has_proc_fd: Path::new("/proc/self/fd").is_dir(),
+            has_cloexec_dup: pipes::pipe_cloexec().and_then(|(read_end, _write_end)| dup_cloexec(read_end.as_raw_fd())).is_ok(),
+            has_memfd_create: false,
+            has_splice: false,
+            has_close_range: false,
+            has_pidfd: false,
+        };
+
+        #[cfg(target_os = "linux")]
+        {
+            let fd = unsafe { libc::memfd_create(c"io-redirect-probe".as_ptr(), libc::MFD_CLOEXEC) };
+            if fd >= 0 {
+                unsafe { libc::close(fd) };
+                capabilities.has_memfd_create = true;
+            }
+
+            // Invalid descriptors fail with EBADF if the syscall exists and ENOSYS otherwise. The
+            // length must not be zero, as a zero-length splice returns 0 before checking them.
+            if unsafe { libc::splice(-1, std::ptr::null_mut(), -1, std::ptr::null_mut(), 1, 0) } < 0 {
+                capabilities.has_splice = io::Error::last_os_error().raw_os_error() == Some(libc::EBADF);
+            }
+
+            // No descriptor can have the highest number, so this closes nothing.
+            capabilities.has_close_range = unsafe { libc::syscall(libc::SYS_close_range, libc::c_uint::MAX, libc::c_uint::MAX, 0 as libc::c_uint) } == 0;
+
+            let pidfd = unsafe { libc::syscall(libc::SYS_pidfd_open, libc::getpid(), 0 as libc::c_uint) };
+            if pidfd >= 0 {
+                unsafe { libc::close(pidfd as RawFd) };
+                capabilities.has_pidfd = true;
+            }
+        }
+
+        return capabilities;
+    }
+
+    /// What a file descriptor currently refers to, as reported by [`current_redirect`].
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub enum RedirectKind
+    {
+        /// A file on a filesystem.
+        File(PathBuf),
+        /// A pipe or FIFO.
+        Pipe,
+        /// A socket.
+        Socket,
+        /// A terminal.
+        Tty,
+        /// A device other than a terminal, such as `/dev/null`.
+        Device,
+        /// Anything else, described by the OS where possible.
+        Other(String),
+    }
+
+    impl std::fmt::Display for RedirectKind {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            return match self {
+                RedirectKind::File(path) => write!(f, "file {}", path.display()),
+                RedirectKind::Pipe => write!(f, "pipe"),
+                RedirectKind::Socket => write!(f, "socket"),
+                RedirectKind::Tty => write!(f, "tty"),
+                RedirectKind::Device => write!(f, "device"),
+                RedirectKind::Other(description) => write!(f, "{description}"),
+            };
+        }
+    }
+
+    /// Inspects what the descriptor number `fd` currently refers to.
+    ///
+    /// This is useful for verifying that a redirection took effect. The path of a file is read
+    /// from `/proc/self/fd` on Linux, with `F_GETPATH` on macOS and from `/dev/fd` elsewhere.
+    ///
+    /// # Returns
+    /// - `io::Result<RedirectKind>`: The kind of the target if successful, `Err` if `fd` is not open.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use io_redirect::unix::{current_redirect, RedirectKind};
+    ///
+    /// if current_redirect(1).unwrap() == RedirectKind::Device {
+    ///     eprintln!("stdout is probably discarded");
+    /// }
+    /// ```
+    pub fn current_redirect(fd: RawFd) -> io::Result<RedirectKind> {
+        use std::os::unix::fs::FileTypeExt;
+
+        if fd < 0 {
+            return Err(io::Error::from_raw_os_error(libc::EBADF));
+        }
+        let file = ManuallyDrop::new(unsafe { File::from_raw_fd(fd) });
+        let file_type = file.metadata()?.file_type();
+        if file_type.is_fifo() {
+            return Ok(RedirectKind::Pipe);
+        }
+        if file_type.is_socket() {
+            return Ok(RedirectKind::Socket);
+        }
+        if file_type.is_char_device() || file_type.is_block_device() {
+            if unsafe { libc::isatty(fd) } == 1 {
+                return Ok(RedirectKind::Tty);
+            }
+            return Ok(RedirectKind::Device);
+        }
+        return match fd_path(fd) {
+            Some(path) if path.is_absolute() => Ok(RedirectKind::File(path)),
+            Some(path) => Ok(RedirectKind::Other(path.display().to_string())),
+            None => Ok(RedirectKind::Other(String::from("unknown"))),
+        };
+    }
+
+    /// Checks whether `src_fd` refers to the same file as `expected_dst_fd`, e.g. after redirecting one to the other.
+    ///
+    /// The files are compared by device and inode number, so two separately opened descriptors of
+    /// the same file also match.
+    ///
+    /// # Returns
+    /// - `io::Result<bool>`: Whether both refer to the same file if successful, always `false` if
+    ///   either is a pipe or socket as those cannot be told apart this way, and `Err` if either is
+    ///   not open.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use io_redirect::Redirectable;
+    /// use io_redirect::unix::verify_redirect;
+    /// use std::os::fd::AsRawFd;
+    ///
+    /// let log = std::fs::File::create("log.txt").unwrap();
+    /// std::io::stdout().redirect(&log).unwrap();
+    /// debug_assert!(verify_redirect(1, log.as_raw_fd()).unwrap());
+    /// ```
+    pub fn verify_redirect(src_fd: RawFd, expected_dst_fd: RawFd) -> io::Result<bool> {
+        use std::os::unix::fs::FileTypeExt;
+
+        if src_fd < 0 || expected_dst_fd < 0 {
+            return Err(io::Error::from_raw_os_error(libc::EBADF));
+        }
+        let src = ManuallyDrop::new(unsafe { File::from_raw_fd(src_fd) }).metadata()?;
+        let dst = ManuallyDrop::new(unsafe { File::from_raw_fd(expected_dst_fd) }).metadata()?;
+        for file_type in [src.file_type(), dst.file_type()] {
+            if file_type.is_fifo() || file_type.is_socket() {
+                return Ok(false);
+            }
+        }
+        return Ok(src.dev() == dst.dev() && src.ino() == dst.ino());
+    }
+
+    /// Checks whether `fd` currently refers to the file at `expected_path`, e.g. after redirecting it there.
+    ///
+    /// The path of the open file is looked up like in [`current_redirect`] and compared with the
+    /// canonical form of `expected_path`.
+    ///
+    /// # Returns
+    /// - `io::Result<bool>`: Whether `fd` refers to `expected_path`. `Ok(false)` for pipes, sockets,
+    ///   terminals and other descriptors without a meaningful path. `Err` if `fd` is not open or
+    ///   `expected_path` cannot be canonicalized, e.g. because it does not exist.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use io_redirect::{is_redirect_active, redirect_std_to_path};
+    /// use std::path::Path;
+    ///
+    /// redirect_std_to_path("out.log", true).unwrap();
+    /// assert!(is_redirect_active(&std::io::stdout(), Path::new("out.log")).unwrap());
+    /// ```
+    pub fn is_redirect_active<T: Descriptable>(fd: &T, expected_path: &Path) -> io::Result<bool> {
+        return match current_redirect(fd.as_raw_fd())? {
+            RedirectKind::File(path) => Ok(path == expected_path.canonicalize()?),
+            _ => Ok(false),
+        };
+    }
+
+    #[cfg(target_os = "macos")]
+    fn fd_path(fd: RawFd) -> Option<PathBuf> {
+        use std::os::unix::ffi::OsStrExt;
+
+        let mut buf = vec![0u8; libc::PATH_MAX as usize];
+        if unsafe { fcntl(fd, libc::F_GETPATH, buf.as_mut_ptr()) } < 0 {
+            return None;
+        }
+        let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+        return Some(PathBuf::from(std::ffi::OsStr::from_bytes(&buf[..len])));
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    fn fd_path(fd: RawFd) -> Option<PathBuf> {
+        let fd_dir = if cfg!(target_os = "linux") { "/proc/self/fd" } else { "/dev/fd" };
+        return std::fs::read_link(format!("{fd_dir}/{fd}")).ok();
+    }
+
+    /// A directory descriptor number, such as one inherited by a sandboxed process.
+    ///
+    /// `DirFd` does not own the descriptor and never closes it.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub struct DirFd(pub RawFd);
+
+    /// Redirects I/O to the file `name` inside the directory `dir`, opened according to `opts`.
+    ///
+    /// The file is opened with `openat(2)`, so no absolute path of the directory is needed.
+    ///
+    /// # Parameters
+    /// - `source`: The entity to redirect.
+    /// - `dir`: The directory containing the destination.
+    /// - `name`: The path of the destination relative to `dir`.
+    /// - `opts`: How to open the file. The `include_*` fields are ignored.
+    ///
+    /// # Returns
+    /// - `io::Result<()>`: `Ok` if successful, `Err` otherwise. A `name` containing a NUL byte is
+    ///   rejected with `ErrorKind::InvalidInput`.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use io_redirect::RedirectOptions;
+    /// use io_redirect::unix::{redirect_to_path_relative_to, DirFd};
+    /// use std::ffi::OsStr;
+    /// use std::os::fd::AsRawFd;
+    ///
+    /// let dir = std::fs::File::open("/var/log").unwrap();
+    /// let name = OsStr::new("app.log");
+    /// redirect_to_path_relative_to(&mut std::io::stdout(), &DirFd(dir.as_raw_fd()), name, &RedirectOptions::default()).unwrap();
+    /// ```
+    pub fn redirect_to_path_relative_to<T: AsRawFd>(source: &mut T, dir: &DirFd, name: &std::ffi::OsStr, opts: &RedirectOptions) -> io::Result<()> {
+        use std::os::unix::ffi::OsStrExt;
+
+        let name = std::ffi::CString::new(name.as_bytes()).map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        let mode = opts.unix_mode.unwrap_or(0o666) as libc::c_uint;
+
+        let fd = unsafe { libc::openat(dir.0, name.as_ptr(), opts.open_flags(), mode) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let dst = unsafe { OwnedFd::from_raw_fd(fd) };
+        return libc_common::redirect_fd_to_fd(source.as_raw_fd(), dst.as_raw_fd());
+    }
+}
+
+#[cfg(all(unix, feature = "libc_on_unix"))]
+pub use unix::is_redirect_active;
+
+#[cfg(all(windows, feature = "windows-sys"))]
+mod named_pipe
+{
+    use super::*;
+    use std::fs::OpenOptions;
+    use std::io::{Read, Write};
+    use std::os::windows::ffi::OsStrExt;
+    use std::os::windows::io::{AsRawHandle, FromRawHandle, RawHandle};
+    use windows_sys::Win32::Foundation::INVALID_HANDLE_VALUE;
+    use windows_sys::Win32::Storage::FileSystem::{FILE_FLAG_FIRST_PIPE_INSTANCE, PIPE_ACCESS_INBOUND};
+    use windows_sys::Win32::System::Pipes::{CreateNamedPipeW, PIPE_READMODE_BYTE, PIPE_REJECT_REMOTE_CLIENTS, PIPE_TYPE_BYTE, PIPE_WAIT};
+
+    const PIPE_PREFIX: &str = r"\\.\pipe\";
+    const PIPE_BUFFER_SIZE: u32 = 64 * 1024;
+
+    /// The reading end of a pipe created by [`create_named_pipe_pair`].
+    #[derive(Debug)]
+    pub struct NamedPipeServer
+    {
+        file: File,
+    }
+
+    /// The writing end of a pipe created by [`create_named_pipe_pair`].
+    ///
+    /// Redirect a standard stream to it and read what is written from the matching [`NamedPipeServer`].
+    #[derive(Debug)]
+    pub struct NamedPipeClient
+    {
+        file: File,
+    }
+
+    impl Read for NamedPipeServer {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            return self.file.read(buf);
+        }
+    }
+
+    impl AsRawHandle for NamedPipeServer {
+        fn as_raw_handle(&self) -> RawHandle {
+            return self.file.as_raw_handle();
+        }
+    }
+
+    impl Write for NamedPipeClient {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            return self.file.write(buf);
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            return self.file.flush();
+        }
+    }
+
+    impl AsRawHandle for NamedPipeClient {
+        fn as_raw_handle(&self) -> RawHandle {
+            return self.file.as_raw_handle();
+        }
+    }
+
+    /// Creates a connected pair of named pipe ends for sending output to a reader in this process.
+    ///
+    /// Both ends implement `Descriptable`, so `Stdout` and `Stderr` can be redirected to the
+    /// client end while another thread reads from the server end.
+    ///
+    /// # Parameters
+    /// - `name`: The name of the pipe. It is prefixed with `\\.\pipe\` unless it already starts with it.
+    ///
+    /// # Returns
+    /// - `io::Result<(NamedPipeServer, NamedPipeClient)>`: The reading and writing ends if successful,
+    ///   `Err` otherwise, including when a pipe with the same name already exists.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use io_redirect::{create_named_pipe_pair, Redirectable};
+    /// use std::io::Read;
+    ///
+    /// let (mut server, client) = create_named_pipe_pair("my-app-log").unwrap();
+    /// let reader = std::thread::spawn(move || {
+    ///     let mut output = String::new();
+    ///     server.read_to_string(&mut output).map(|_| output)
+    /// });
+    /// std::io::stdout().redirect(&client).unwrap();
+    /// ```
+    ///
+    /// # Notes
+    /// The server end only sees the end of the data once every handle to the client end is closed.
+    /// Since `SetStdHandle` does not duplicate the handle, redirect the stream elsewhere before
+    /// dropping the client end.
+    pub fn create_named_pipe_pair(name: &str) -> io::Result<(NamedPipeServer, NamedPipeClient)> {
+        let full_name = if name.starts_with(PIPE_PREFIX) { name.to_string() } else { format!("{PIPE_PREFIX}{name}") };
+        let wide_name: Vec<u16> = std::ffi::OsStr::new(&full_name).encode_wide().chain(std::iter::once(0)).collect();
+
+        let server_handle = unsafe {
+            CreateNamedPipeW(
+                wide_name.as_ptr(),
+                PIPE_ACCESS_INBOUND | FILE_FLAG_FIRST_PIPE_INSTANCE,
+                PIPE_TYPE_BYTE | PIPE_READMODE_BYTE | PIPE_WAIT | PIPE_REJECT_REMOTE_CLIENTS,
+                1,
+                PIPE_BUFFER_SIZE,
+                PIPE_BUFFER_SIZE,
+                0,
+                std::ptr::null(),
+            )
+        };
+        if server_handle == INVALID_HANDLE_VALUE {
+            return Err(io::Error::last_os_error());
+        }
+        let server = NamedPipeServer { file: unsafe { File::from_raw_handle(server_handle as _) } };
+
+        // A client may connect before the server calls ConnectNamedPipe, so opening it here is enough.
+        let client = NamedPipeClient { file: OpenOptions::new().write(true).open(&full_name)? };
+        return Ok((server, client));
+    }
+}
+
+#[cfg(all(windows, feature = "windows-sys"))]
+pub use named_pipe::*;
+
+#[cfg(all(feature = "std", any(all(unix, feature = "libc_on_unix"), all(windows, feature = "windows-sys"))))]
+mod convenience
+{
+    use super::*;
+    use std::fs::OpenOptions;
+    use std::io::{stderr, stdin, stdout};
+    use std::path::Path;
+    pub fn redirect_std_to_path<P: AsRef<Path>>(destination: P, append: bool) -> io::Result<()> {
+        return redirect_std_to_path_with_options(destination, &RedirectOptions { append, include_stdout: true, include_stderr: true, ..Default::default() });
+    }
+
+    /// Redirects the standard streams selected by `opts` to the file at `path`, opened according to `opts`.
+    ///
+    /// # Parameters
+    /// - `path`: The path of the file to redirect to.
+    /// - `opts`: How to open the file and which streams to redirect.
+    ///
+    /// # Returns
+    /// - `io::Result<()>`: `Ok` if successful, `Err` otherwise.
+    ///
+    /// # Notes
+    /// The streams are redirected in the order stdin, stdout, stderr. If one of them fails, the
+    /// streams before it stay redirected and the ones after it are left untouched.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use io_redirect::{redirect_std_to_path_with_options, RedirectOptions};
+    /// use std::path::Path;
+    ///
+    /// let opts = RedirectOptions { append: false, truncate: true, include_stderr: false, ..Default::default() };
+    /// redirect_std_to_path_with_options(Path::new("out.log"), &opts).unwrap();
+    /// ```
+    pub fn redirect_std_to_path_with_options<P: AsRef<Path>>(path: P, opts: &RedirectOptions) -> io::Result<()> {
+        let dst = opts.open(path.as_ref())?;
+        if opts.include_stdin {
+            stdin().redirect(&dst)?;
+        }
+        if opts.include_stdout {
+            stdout().redirect(&dst)?;
+        }
+        if opts.include_stderr {
+            stderr().redirect(&dst)?;
+        }
+        if cfg!(not(unix)) {
+            std::mem::forget(dst);
+        }
+        return Ok(());
+    }
+
+    /// Like [`redirect_std_to_path_with_options`], but reports which stream failed.
+    ///
+    /// # Parameters
+    /// - `path`: The path of the file to redirect to.
+    /// - `opts`: How to open the file and which streams to redirect.
+    ///
+    /// # Returns
+    /// - `Result<(), RedirectError>`: `Ok` if successful. Otherwise
+    ///   `RedirectError::OpenDestinationFailed` if the file cannot be opened, or
+    ///   `RedirectError::PartialRedirect` naming the streams that stay redirected and the one that
+    ///   failed.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use io_redirect::{try_redirect_std_to_path_with_options, RedirectError, RedirectOptions};
+    /// use std::path::Path;
+    ///
+    /// match try_redirect_std_to_path_with_options(Path::new("out.log"), &RedirectOptions::default()) {
+    ///     Err(RedirectError::PartialRedirect { completed, .. }) => assert!(completed.len() < 2),
+    ///     result => result.unwrap(),
+    /// }
+    /// ```
+    pub fn try_redirect_std_to_path_with_options<P: AsRef<Path>>(path: P, opts: &RedirectOptions) -> Result<(), RedirectError> {
+        let path = path.as_ref();
+        let dst = opts.open(path).map_err(|source| RedirectError::OpenDestinationFailed { path: path.to_path_buf(), source })?;
+        let partial = |completed: &[&str], failed_at: &str, source: io::Error| RedirectError::PartialRedirect {
+            completed: completed.iter().map(|name| name.to_string()).collect(),
+            failed_at: failed_at.to_string(),
+            source,
+        };
+        let mut completed = Vec::new();
+        if opts.include_stdin {
+            stdin().redirect(&dst).map_err(|source| partial(&completed, "stdin", source))?;
+            completed.push("stdin");
+        }
+        if opts.include_stdout {
+            stdout().redirect(&dst).map_err(|source| partial(&completed, "stdout", source))?;
+            completed.push("stdout");
+        }
+        if opts.include_stderr {
+            stderr().redirect(&dst).map_err(|source| partial(&completed, "stderr", source))?;
+        }
+        if cfg!(not(unix)) {
+            std::mem::forget(dst);
+        }
+        return Ok(());
+    }
+
+    /// Redirects stdout and, if `include_stderr` is set, stderr to a new file at `path`.
+    ///
+    /// The file is created with `O_CREAT | O_EXCL` semantics, so an existing file is never
+    /// appended to or overwritten. Callers that need a fresh file per session should generate a
+    /// unique name, e.g. one containing a timestamp.
+    ///
+    /// # Parameters
+    /// - `path`: The path of the file to create.
+    /// - `include_stderr`: Whether to redirect stderr too.
+    ///
+    /// # Returns
+    /// - `io::Result<()>`: `Ok` if successful, `Err` with `ErrorKind::AlreadyExists` if the file
+    ///   already exists, or another `Err` otherwise.
+    pub fn redirect_std_to_path_exclusive<P: AsRef<Path>>(path: P, include_stderr: bool) -> io::Result<()> {
+        return redirect_std_to_path_with_options(path, &RedirectOptions { append: false, create_new: true, include_stderr, ..Default::default() });
+    }
+
+    /// Redirects stdout and stderr to the file at `path` like [`redirect_std_to_path`], creating it
+    /// under the file mode creation mask `umask`.
+    ///
+    /// The process umask is replaced with `umask` while the file is opened and restored afterwards.
+    /// This keeps the permissions of a new log file predictable when the inherited umask was set
+    /// by a container or init system. An existing file keeps its permissions. On Windows, `umask`
+    /// is ignored.
+    ///
+    /// <div class="warning">
+    /// The umask is process-global. Files created concurrently by other threads while this function
+    /// runs get their permissions from `umask` as well, so only call it when no other code creates files.
+    /// </div>
+    ///
+    /// # Parameters
+    /// - `path`: The path of the file to redirect to.
+    /// - `append`: Whether to append to an existing file rather than overwrite it from the start.
+    /// - `umask`: The mask applied to the mode of a newly created file, such as `0o077`.
+    ///
+    /// # Returns
+    /// - `io::Result<()>`: `Ok` if successful, `Err` otherwise.
+    pub fn redirect_std_to_path_with_umask<P: AsRef<Path>>(path: P, append: bool, umask: u32) -> io::Result<()> {
+        #[cfg(unix)]
+        {
+            let previous = unsafe { libc::umask(umask as libc::mode_t) };
+            let result = redirect_std_to_path(path, append);
+            unsafe { libc::umask(previous) };
+            return result;
+        }
+
+        #[cfg(not(unix))]
+        {
+            let _ = umask;
+            return redirect_std_to_path(path, append);
+        }
+    }
+
+    /// Redirects stdout to the file at `stdout_path` and stderr to the file at `stderr_path`.
+    ///
+    /// Both files are opened before either stream is redirected, so a bad path leaves both
+    /// streams untouched.
+    ///
+    /// # Notes
+    /// If redirecting stderr fails, stdout stays redirected.
+    pub fn redirect_std_pair<P1: AsRef<Path>, P2: AsRef<Path>>(stdout_path: P1, stderr_path: P2, append: bool) -> io::Result<()> {
+        let opts = RedirectOptions { append, ..Default::default() };
+        let stdout_dst = opts.open(stdout_path.as_ref())?;
+        let stderr_dst = opts.open(stderr_path.as_ref())?;
+        stdout().redirect(&stdout_dst)?;
+        stderr().redirect(&stderr_dst)?;
+        if cfg!(not(unix)) {
+            std::mem::forget(stdout_dst);
+            std::mem::forget(stderr_dst);
+        }
+        return Ok(());
+    }
+
+    /// Redirects stdout and, if `include_stderr` is set, stderr to an already opened `file`.
+    ///
+    /// This is useful when the file needs flags or permissions that `redirect_std_to_path` does
+    /// not provide, or when `file` is also used for other purposes.
+    ///
+    /// # Notes
+    /// On Windows the standard handles refer to the handle of `file` itself, so `file` must stay
+    /// open for as long as the redirection is in use. On Unix it may be closed right away.
+    pub fn redirect_std_to_file(file: &File, include_stderr: bool) -> io::Result<()> {
+        stdout().redirect(file)?;
+        if include_stderr {
+            stderr().redirect(file)?;
+        }
+        return Ok(());
+    }
+
+    /// Redirects stdout to wherever stderr currently goes, like `1>&2` in a shell.
+    pub fn redirect_stdout_to_stderr() -> io::Result<()> {
+        return stdout().redirect(&stderr());
+    }
+
+    /// Redirects stderr to wherever stdout currently goes, like `2>&1` in a shell.
+    pub fn redirect_stderr_to_stdout() -> io::Result<()> {
+        return stderr().redirect(&stdout());
+    }
+
+    /// Redirects stdin so that it reads from the file at `path`.
+    ///
+    /// # Notes
+    /// Input already buffered by `std::io::stdin()` before the call is still returned first.
+    pub fn redirect_stdin_from_path<P: AsRef<Path>>(path: P) -> io::Result<()> {
+        let src = OpenOptions::new().read(true).open(path)?;
+        stdin().redirect(&src)?;
+        if cfg!(not(unix)) {
+            std::mem::forget(src);
+        }
+        return Ok(());
+    }
+
+    /// Redirects stdin to read from the file named by the environment variable `var`, if it is set.
+    ///
+    /// # Returns
+    /// - `io::Result<Option<()>>`: `Ok(Some(()))` if stdin was redirected, `Ok(None)` if `var` is
+    ///   not set, or `Err` if the file could not be opened or stdin could not be redirected.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use io_redirect::redirect_stdin_from_env;
+    ///
+    /// if redirect_stdin_from_env("APP_INPUT").unwrap().is_none() {
+    ///     eprintln!("APP_INPUT not set, reading from the terminal");
+    /// }
+    /// ```
+    pub fn redirect_stdin_from_env(var: &str) -> io::Result<Option<()>> {
+        return match std::env::var_os(var) {
+            Some(path) => redirect_stdin_from_path(path).map(Some),
+            None => Ok(None),
+        };
+    }
+
+    /// Redirects stdout and stderr to the files named by the environment variables `stdout_var`
+    /// and `stderr_var`, each only if it is set.
+    ///
+    /// If both variables name the same path, the file is opened once and shared by both streams,
+    /// so their output is interleaved in order.
+    ///
+    /// # Parameters
+    /// - `stdout_var`: The variable holding the path for stdout.
+    /// - `stderr_var`: The variable holding the path for stderr.
+    /// - `append`: Whether to append to existing files rather than overwrite them from the start.
+    ///
+    /// # Returns
+    /// - `io::Result<usize>`: The number of redirected streams, `0` if neither variable is set,
+    ///   or `Err` if a file could not be opened or a stream could not be redirected.
+    ///
+    /// # Notes
+    /// Both files are opened before either stream is redirected. If redirecting stderr fails,
+    /// stdout stays redirected.
+    pub fn redirect_std_to_env(stdout_var: &str, stderr_var: &str, append: bool) -> io::Result<usize> {
+        let opts = RedirectOptions { append, ..Default::default() };
+        let stdout_path = std::env::var_os(stdout_var);
+        let stderr_path = std::env::var_os(stderr_var);
+
+        let stdout_dst = stdout_path.as_ref().map(|path| opts.open(Path::new(path))).transpose()?;
+        let stderr_dst = match (&stderr_path, &stdout_dst) {
+            (Some(path), Some(shared)) if stdout_path.as_ref() == Some(path) => Some(shared.try_clone()?),
+            (Some(path), _) => Some(opts.open(Path::new(path))?),
+            (None, _) => None,
+        };
+
+        let mut redirected = 0;
+        if let Some(dst) = stdout_dst {
+            stdout().redirect(&dst)?;
+            redirected += 1;
+            if cfg!(not(unix)) {
+                std::mem::forget(dst);
+            }
+        }
+        if let Some(dst) = stderr_dst {
+            stderr().redirect(&dst)?;
+            redirected += 1;
+            if cfg!(not(unix)) {
+                std::mem::forget(dst);
+            }
+        }
+        return Ok(redirected);
+    }
+
+    const NULL_DEVICE: &str = if cfg!(windows) { "NUL" } else { "/dev/null" };
+
+    /// Redirects stdout and stderr to the file at `destination` and stdin to the null device.
+    ///
+    /// Both files are opened before any stream is redirected, so a bad `destination` leaves all
+    /// three streams untouched.
+    ///
+    /// # Notes
+    /// The streams are redirected in the order stdin, stdout, stderr. If one of them fails, the
+    /// streams before it stay redirected and the ones after it are left untouched.
+    pub fn redirect_all_std_to_path<P: AsRef<Path>>(destination: P, append: bool) -> io::Result<()> {
+        let null = OpenOptions::new().read(true).open(NULL_DEVICE)?;
+        let dst = OpenOptions::new().read(false).write(true).create(true).append(append).open(destination)?;
+        stdin().redirect(&null)?;
+        stdout().redirect(&dst)?;
+        stderr().redirect(&dst)?;
+        if cfg!(not(unix)) {
+            std::mem::forget(null);
+            std::mem::forget(dst);
+        }
+        return Ok(());
+    }
+
+    /// Redirects `source` to the platform null device (`/dev/null` or `NUL`).
+    ///
+    /// On Unix the null device is closed again once `source` refers to it.
+    ///
+    /// This is the redirect counterpart of `std::io::sink()`. `io::Sink` itself cannot be a
+    /// destination: an `impl Redirectable<io::Sink>` would overlap with the descriptor-based one
+    /// on Unix-like platforms, as std may implement `AsRawFd` for it in the future.
+    #[doc(alias = "sink")]
+    pub fn redirect_to_null<T: Redirectable<File>>(source: &mut T) -> io::Result<()> {
+        let null = OpenOptions::new().read(true).write(true).open(NULL_DEVICE)?;
+        source.redirect(&null)?;
+        if cfg!(not(unix)) {
+            std::mem::forget(null);
+        }
+        return Ok(());
+    }
+
+    /// Redirects stdout, stderr and, if `include_stdin` is set, stdin to the platform null device.
+    ///
+    /// The null device is opened once for reading and writing and shared by all streams.
+    ///
+    /// # Notes
+    /// The streams are redirected in the order stdin, stdout, stderr. If one of them fails, the
+    /// streams before it stay redirected.
+    pub fn redirect_std_to_null(include_stdin: bool) -> io::Result<()> {
+        let null = OpenOptions::new().read(true).write(true).open(NULL_DEVICE)?;
+        if include_stdin {
+            stdin().redirect(&null)?;
+        }
+        stdout().redirect(&null)?;
+        stderr().redirect(&null)?;
+        if cfg!(not(unix)) {
+            std::mem::forget(null);
+        }
+        return Ok(());
+    }
+
+    const TTY_OUTPUT_DEVICE: &str = if cfg!(windows) { "CONOUT$" } else { "/dev/tty" };
+    const TTY_INPUT_DEVICE: &str = if cfg!(windows) { "CONIN$" } else { "/dev/tty" };
+
+    /// Redirects stdout, stderr and, if `include_stdin` is set, stdin back to the terminal.
+    ///
+    /// This opens the controlling terminal (`/dev/tty`) on Unix and the console (`CONOUT$` and
+    /// `CONIN$`) on Windows, which undoes earlier redirections such as `redirect_std_to_path`.
+    ///
+    /// # Returns
+    /// - `io::Result<()>`: `Ok` if successful, `Err` with `ErrorKind::NotConnected` if there is no
+    ///   terminal to attach to, or another `Err` if opening the terminal or a redirection fails.
+    pub fn reset_std_to_tty(include_stdin: bool) -> io::Result<()> {
+        let output = open_tty(TTY_OUTPUT_DEVICE)?;
+        let input = if include_stdin {
+            Some(open_tty(TTY_INPUT_DEVICE)?)
+        } else {
+            None
+        };
+        if let Some(input) = &input {
+            stdin().redirect(input)?;
+        }
+        stdout().redirect(&output)?;
+        stderr().redirect(&output)?;
+        if cfg!(not(unix)) {
+            std::mem::forget(output);
+            std::mem::forget(input);
+        }
+        return Ok(());
+    }
+
+    /// Opens a terminal device, reporting a missing terminal as `ErrorKind::NotConnected` and any
+    /// other failure as is.
+    fn open_tty(device: &str) -> io::Result<File> {
+        return OpenOptions::new().read(true).write(true).open(device).map_err(|error| {
+            let no_terminal = error.kind() == io::ErrorKind::NotFound;
+            #[cfg(unix)]
+            let no_terminal = no_terminal || error.raw_os_error() == Some(libc::ENXIO);
+            if no_terminal {
+                return io::Error::new(io::ErrorKind::NotConnected, error);
+            }
+            return error;
+        });
+    }
+
+    /// Redirects stdin, stdout and stderr to the platform null device, as daemons do after `fork()`.
+    ///
+    /// The null device is opened once for reading and writing and shared by all three streams.
+    ///
+    /// # Notes
+    /// The streams are redirected in the order stdin, stdout, stderr. If opening the null device
+    /// fails, no stream is redirected. If redirecting a stream fails, the streams before it stay
+    /// redirected and the ones after it are left untouched.
+    pub fn stdio_to_devnull() -> io::Result<()> {
+        return redirect_std_to_null(true);
+    }
+
+    /// Redirects `source` to `dest` unless `source` is a terminal.
+    ///
+    /// This lets a service log to a file when run under a service manager while still printing
+    /// to the terminal during development.
+    ///
+    /// # Parameters
+    /// - `source`: The entity to redirect, typically a standard stream.
+    /// - `dest`: A reference to the target destination.
+    ///
+    /// # Returns
+    /// - `io::Result<bool>`: `true` if `source` was redirected, `false` if it is a terminal and was
+    ///   left untouched, or `Err` if the redirection failed.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use io_redirect::redirect_if_not_tty;
+    /// use std::path::Path;
+    ///
+    /// if redirect_if_not_tty(&mut std::io::stdout(), Path::new("/var/log/app.log")).unwrap() {
+    ///     eprintln!("stdout goes to /var/log/app.log");
+    /// }
+    /// ```
+    pub fn redirect_if_not_tty<T: Redirectable<U> + Descriptable, U: ?Sized>(source: &mut T, dest: &U) -> io::Result<bool> {
+        if is_tty(source) {
+            return Ok(false);
+        }
+        source.redirect(dest)?;
+        return Ok(true);
+    }
+
+    #[cfg(unix)]
+    fn is_tty<T: Descriptable>(fd: &T) -> bool {
+        return unsafe { libc::isatty(fd.as_raw_fd()) } == 1;
+    }
+
+    #[cfg(windows)]
+    fn is_tty<T: Descriptable>(fd: &T) -> bool {
+        use windows_sys::Win32::System::Console::GetConsoleMode;
+
+        let mut mode = 0;
+        return unsafe { GetConsoleMode(fd.as_raw_handle() as _, &mut mode) } != 0;
+    }
+}
+
+#[cfg(all(feature = "std", any(all(unix, feature = "libc_on_unix"), all(windows, feature = "windows-sys"))))]
+pub use convenience::*;
+pub use platform::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(any(all(unix, feature = "libc_on_unix"), all(windows, feature = "std", feature = "libc_on_windows")))]
+    use std::fs::OpenOptions;
+    use std::io::{Read, Write};
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    use std::mem::ManuallyDrop;
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    use libc::close;
+
+    #[cfg(any(all(unix, feature = "libc_on_unix"), all(windows, any(feature = "libc_on_windows", feature = "windows-sys"))))]
+    #[test]
+    fn redirects_file_to_file() {
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut file1 = File::create(tempdir.path().join("file1.txt")).unwrap();
+        let mut file2 = File::create(tempdir.path().join("file2.txt")).unwrap();
+
+        // Act
+        file1.redirect(&file2).unwrap();
+        file1.write_all(b"Hello,").unwrap();
+        file1.flush().unwrap();
+        file2.write_all(b" World!").unwrap();
+        file2.flush().unwrap();
+
+        // Assert
+        let mut dst_file = File::open(tempdir.path().join("file2.txt")).unwrap();
+        let mut dst_contents = String::new();
+        dst_file.read_to_string(&mut dst_contents).unwrap();
+        assert_eq!(dst_contents, "Hello, World!");
+
+        let mut old_file1_contents = String::new();
+        let mut old_file1 = File::open(tempdir.path().join("file1.txt")).unwrap();
+        old_file1.read_to_string(&mut old_file1_contents).unwrap();
+        assert_eq!(old_file1_contents, "");
+    }
+
+    #[cfg(all(feature = "std", any(all(unix, feature = "libc_on_unix"), all(windows, feature = "libc_on_windows"))))]
+    #[test]
+    fn redirects_file_to_path() {
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let src_path = tempdir.path().join("src.txt");
+        let dst_path = tempdir.path().join("dst.txt");
+        let mut src = OpenOptions::new().create(true).read(true).write(true).open(&src_path).unwrap();
+
+        // Act
+        src.redirect(dst_path.as_path()).unwrap();
+        src.write_all(b"abc").unwrap();
+        src.flush().unwrap();
+
+        // Assert
+        let mut dst_contents = String::new();
+        File::open(&dst_path).unwrap().read_to_string(&mut dst_contents).unwrap();
+        assert_eq!(dst_contents, "abc");
+
+        let mut original_contents = String::new();
+        File::open(&src_path).unwrap().read_to_string(&mut original_contents).unwrap();
+        assert_eq!(original_contents, "");
+    }
+
+    #[cfg(all(feature = "std", any(all(unix, feature = "libc_on_unix"), all(windows, feature = "libc_on_windows"))))]
+    #[test]
+    fn errors_on_redirect_to_directory() {
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let dir_path = tempdir.path();
+        let mut src = File::create(dir_path.join("somefile.txt")).unwrap();
+
+        // Act
+        let err = src.redirect(dir_path).unwrap_err();
+
+        // Assert
+        assert!(err.raw_os_error().is_some());
+    }
+
+    #[cfg(all(feature = "std", any(all(unix, feature = "libc_on_unix"), all(windows, feature = "libc_on_windows"))))]
+    #[test]
+    fn errors_on_redirect_with_missing_parent_directory() {
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut src = File::create(tempdir.path().join("s.txt")).unwrap();
+        let bad_path = tempdir.path().join("no_such_dir").join("f.txt");
+
+        // Act
+        let err = src.redirect(bad_path.as_path()).unwrap_err();
+
+        // Assert
+        assert!(err.raw_os_error().is_some());
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn errors_on_redirect_to_closed_fd() {
+        use std::os::fd::{IntoRawFd, OwnedFd};
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut src_file = File::create(tempdir.path().join("src.txt")).unwrap();
+        let dst_fd = DupFd::from(OwnedFd::from(File::create(tempdir.path().join("dst.txt")).unwrap()));
+        unsafe { close(*dst_fd) };
+
+        // Act
+        let err = src_file.redirect(&dst_fd).unwrap_err();
+
+        // Assert
+        assert!(err.raw_os_error().is_some());
+        // The number may already be reused by another test thread, so it must not be closed again.
+        let _ = dst_fd.into_raw_fd();
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn hijacks_fd_number_until_guard_is_dropped() {
+        use std::os::fd::AsRawFd;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut victim = File::create(tempdir.path().join("victim.txt")).unwrap();
+        let intruder = File::create(tempdir.path().join("intruder.txt")).unwrap();
+
+        // Act
+        let guard = unix::hijack_fd_number(victim.as_raw_fd(), intruder.as_raw_fd()).unwrap();
+        victim.write_all(b"hijacked").unwrap();
+        drop(guard);
+        victim.write_all(b"restored").unwrap();
+
+        // Assert
+        let victim_contents = std::fs::read_to_string(tempdir.path().join("victim.txt")).unwrap();
+        assert_eq!(victim_contents, "restored");
+        let intruder_contents = std::fs::read_to_string(tempdir.path().join("intruder.txt")).unwrap();
+        assert_eq!(intruder_contents, "hijacked");
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn errors_on_hijack_of_closed_fd_number() {
+        use std::os::fd::AsRawFd;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let dst_file = File::create(tempdir.path().join("dst.txt")).unwrap();
+
+        // Act
+        let err = unix::hijack_fd_number(-1, dst_file.as_raw_fd()).err().unwrap();
+
+        // Assert
+        assert!(err.raw_os_error().is_some());
+    }
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn migrates_all_fds_of_old_file_to_new_file() {
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let old_path = tempdir.path().join("old.log");
+        let new_path = tempdir.path().join("new.log");
+        let mut first = File::create(&old_path).unwrap();
+        let mut second = OpenOptions::new().append(true).open(&old_path).unwrap();
+        let mut unrelated = File::create(tempdir.path().join("unrelated.log")).unwrap();
+
+        // Act
+        let migrated = unix::migrate_redirect(old_path.as_path(), new_path.as_path()).unwrap();
+        first.write_all(b"first ").unwrap();
+        second.write_all(b"second").unwrap();
+        unrelated.write_all(b"unrelated").unwrap();
+
+        // Assert
+        assert_eq!(migrated, 2);
+        assert_eq!(std::fs::read_to_string(&old_path).unwrap(), "");
+        assert_eq!(std::fs::read_to_string(&new_path).unwrap(), "first second");
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("unrelated.log")).unwrap(), "unrelated");
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn acquires_preallocated_fds_until_refilled() {
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let log_path = tempdir.path().join("pool.log");
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let mut pool = unix::FdPool::preallocate(log_path.as_path(), 2).unwrap();
+
+        // Act
+        let first = pool.acquire().unwrap();
+        let second = pool.acquire().unwrap();
+        let exhausted = pool.acquire().is_none();
+        pool.refill().unwrap();
+        let refilled = pool.acquire().is_some();
+        src.redirect(&first).unwrap();
+        src.write_all(b"pooled").unwrap();
+        drop(second);
+
+        // Assert
+        assert!(exhausted);
+        assert!(refilled);
+        assert_eq!(std::fs::read_to_string(&log_path).unwrap(), "pooled");
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn proxies_all_bytes_between_fds() {
+        use std::os::fd::OwnedFd;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        std::fs::write(tempdir.path().join("in.txt"), b"proxied through a tiny buffer").unwrap();
+        let read_fd = OwnedFd::from(File::open(tempdir.path().join("in.txt")).unwrap());
+        let write_fd = OwnedFd::from(File::create(tempdir.path().join("out.txt")).unwrap());
+
+        // Act
+        let forwarded = unix::FdProxy::new(read_fd, write_fd).with_buf_size(4).run_async().join().unwrap().unwrap();
+
+        // Assert
+        assert_eq!(forwarded, 29);
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("out.txt")).unwrap(), "proxied through a tiny buffer");
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn remaps_fd_cycle_and_chain_without_clobbering() {
+        use std::os::fd::AsRawFd;
+        use unix::{FdNumber, FdRemap};
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut a = File::create(tempdir.path().join("a.txt")).unwrap();
+        let mut b = File::create(tempdir.path().join("b.txt")).unwrap();
+        let mut c = File::create(tempdir.path().join("c.txt")).unwrap();
+        let (fd_a, fd_b, fd_c) = (FdNumber(a.as_raw_fd()), FdNumber(b.as_raw_fd()), FdNumber(c.as_raw_fd()));
+
+        // Act
+        FdRemap::new().add(fd_a, fd_b).add(fd_b, fd_c).add(fd_c, fd_a).apply().unwrap();
+        a.write_all(b"a").unwrap();
+        b.write_all(b"b").unwrap();
+        c.write_all(b"c").unwrap();
+
+        // Assert
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("a.txt")).unwrap(), "b");
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("b.txt")).unwrap(), "c");
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("c.txt")).unwrap(), "a");
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn errors_on_remap_with_conflicting_targets() {
+        use unix::{FdNumber, FdRemap};
+        // Arrange
+        let remap = FdRemap::new().add(FdNumber(10), FdNumber(1)).add(FdNumber(11), FdNumber(1)).add(FdNumber(12), FdNumber(2));
+
+        // Act
+        let err = remap.clone().validate().unwrap_err();
+        let apply_err = remap.apply().unwrap_err();
+
+        // Assert
+        assert_eq!(err.conflicts, vec![(FdNumber(10), FdNumber(1)), (FdNumber(11), FdNumber(1))]);
+        assert_eq!(apply_err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn restores_original_destination_when_guard_is_dropped() {
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let dst = File::create(tempdir.path().join("dst.txt")).unwrap();
+
+        // Act
+        let guard = src.save_and_redirect(&dst).unwrap();
+        src.write_all(b"redirected").unwrap();
+        drop(guard);
+        src.write_all(b"restored").unwrap();
+
+        // Assert
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("src.txt")).unwrap(), "restored");
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("dst.txt")).unwrap(), "redirected");
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn keeps_redirection_when_guard_is_discarded() {
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let dst = File::create(tempdir.path().join("dst.txt")).unwrap();
+
+        // Act
+        src.save_and_redirect(&dst).unwrap().discard();
+        src.write_all(b"still redirected").unwrap();
+
+        // Assert
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("src.txt")).unwrap(), "");
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("dst.txt")).unwrap(), "still redirected");
+    }
+
+    #[cfg(all(feature = "std", target_os = "linux", feature = "libc_on_unix"))]
+    #[test]
+    fn does_not_leak_fds_when_redirecting_to_path_twice() {
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let first_path = tempdir.path().join("first.txt");
+        let second_path = tempdir.path().join("second.txt");
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let fds_pointing_to = |path: &std::path::Path| {
+            unix::list_open_fds().unwrap().into_iter()
+                .filter(|fd| std::fs::read_link(format!("/proc/self/fd/{fd}")).is_ok_and(|target| target == path))
+                .count()
+        };
+
+        // Act
+        src.redirect(first_path.as_path()).unwrap();
+        src.redirect(second_path.as_path()).unwrap();
+
+        // Assert
+        assert_eq!(fds_pointing_to(&first_path), 0);
+        assert_eq!(fds_pointing_to(&second_path), 1);
+    }
+
+    #[cfg(all(feature = "std", any(all(unix, feature = "libc_on_unix"), all(windows, feature = "libc_on_windows"))))]
+    #[test]
+    fn redirects_file_to_path_with_owned_handle() {
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let dst_path = tempdir.path().join("dst.txt");
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+
+        // Act
+        let handle = redirect_to_path_owning(&mut src, &dst_path).unwrap();
+        src.write_all(b"abc").unwrap();
+        handle.close();
+
+        // Assert
+        assert_eq!(std::fs::read_to_string(&dst_path).unwrap(), "abc");
+    }
+
+    #[cfg(all(feature = "std", any(all(unix, feature = "libc_on_unix"), all(windows, feature = "libc_on_windows", feature = "windows-sys"))))]
+    #[test]
+    fn redirects_file_to_null() {
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let src_path = tempdir.path().join("src.txt");
+        let mut src = File::create(&src_path).unwrap();
+
+        // Act
+        redirect_to_null(&mut src).unwrap();
+        src.write_all(b"discarded").unwrap();
+        src.flush().unwrap();
+
+        // Assert
+        assert_eq!(std::fs::read_to_string(&src_path).unwrap(), "");
+    }
+
+    #[cfg(all(feature = "std", any(all(unix, feature = "libc_on_unix"), all(windows, feature = "libc_on_windows"))))]
+    #[test]
+    fn redirects_file_to_path_with_truncation() {
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let dst_path = tempdir.path().join("dst.txt");
+        std::fs::write(&dst_path, "old contents").unwrap();
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let opts = RedirectOptions { append: false, truncate: true, ..Default::default() };
+
+        // Act
+        redirect_to_path_with_options(&mut src, &dst_path, &opts).unwrap();
+        src.write_all(b"new").unwrap();
+
+        // Assert
+        assert_eq!(std::fs::read_to_string(&dst_path).unwrap(), "new");
+    }
+
+    #[cfg(all(feature = "std", any(all(unix, feature = "libc_on_unix"), all(windows, feature = "libc_on_windows"))))]
+    #[test]
+    fn errors_on_redirect_to_existing_path_with_create_new() {
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let dst_path = tempdir.path().join("dst.txt");
+        std::fs::write(&dst_path, "").unwrap();
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let opts = RedirectOptions { create_new: true, ..Default::default() };
+
+        // Act
+        let err = redirect_to_path_with_options(&mut src, &dst_path, &opts).unwrap_err();
+
+        // Assert
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+    }
+
+    #[cfg(all(feature = "std", unix, feature = "libc_on_unix"))]
+    #[test]
+    fn creates_redirect_destination_with_unix_mode() {
+        use std::os::unix::fs::PermissionsExt;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let dst_path = tempdir.path().join("dst.txt");
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let opts = RedirectOptions { unix_mode: Some(0o600), ..Default::default() };
+
+        // Act
+        redirect_to_path_with_options(&mut src, &dst_path, &opts).unwrap();
+
+        // Assert
+        assert_eq!(std::fs::metadata(&dst_path).unwrap().permissions().mode() & 0o777, 0o600);
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn redirects_borrowed_descriptor() {
+        use std::os::fd::AsFd;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let dst = File::create(tempdir.path().join("dst.txt")).unwrap();
+
+        // Act
+        redirect_descriptor(&src.as_fd(), &dst.as_fd()).unwrap();
+        src.write_all(b"abc").unwrap();
+
+        // Assert
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("dst.txt")).unwrap(), "abc");
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix", feature = "raw-fd"))]
+    #[test]
+    fn redirects_raw_fd() {
+        use std::os::fd::AsRawFd;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let dst = File::create(tempdir.path().join("dst.txt")).unwrap();
+
+        // Act
+        unsafe { redirect_raw_fd(src.as_raw_fd(), dst.as_raw_fd()) }.unwrap();
+        src.write_all(b"abc").unwrap();
+
+        // Assert
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("dst.txt")).unwrap(), "abc");
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn redirects_fd_ref_to_fd_ref() {
+        use std::os::fd::AsRawFd;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut src_file = File::create(tempdir.path().join("src.txt")).unwrap();
+        let dst_file = File::create(tempdir.path().join("dst.txt")).unwrap();
+        let mut src = FdRef::try_from_raw_fd(src_file.as_raw_fd()).unwrap();
+        let dst = FdRef::try_from_raw_fd(dst_file.as_raw_fd()).unwrap();
+
+        // Act
+        src.redirect(&dst).unwrap();
+        src_file.write_all(b"abc").unwrap();
+
+        // Assert
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("dst.txt")).unwrap(), "abc");
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn errors_on_fd_ref_from_invalid_fd() {
+        // Act
+        let negative = FdRef::try_from_raw_fd(-1).unwrap_err();
+        let unopened = FdRef::try_from_raw_fd(i32::MAX).unwrap_err();
+
+        // Assert
+        assert_eq!(negative.raw_os_error(), Some(libc::EBADF));
+        assert_eq!(unopened.raw_os_error(), Some(libc::EBADF));
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn pops_redirections_in_reverse_order() {
+        use std::os::fd::AsRawFd;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let dst1 = File::create(tempdir.path().join("dst1.txt")).unwrap();
+        let dst2 = File::create(tempdir.path().join("dst2.txt")).unwrap();
+        let dst3 = File::create(tempdir.path().join("dst3.txt")).unwrap();
+        let mut stack = RedirectStack::new();
+
+        // Act
+        stack.push(src.as_raw_fd(), dst1.as_raw_fd()).unwrap();
+        stack.push(src.as_raw_fd(), dst2.as_raw_fd()).unwrap();
+        stack.push(src.as_raw_fd(), dst3.as_raw_fd()).unwrap();
+        src.write_all(b"3").unwrap();
+        stack.pop().unwrap();
+        src.write_all(b"2").unwrap();
+        stack.pop().unwrap();
+        src.write_all(b"1").unwrap();
+        stack.pop().unwrap();
+        src.write_all(b"0").unwrap();
+        let err = stack.pop().unwrap_err();
+
+        // Assert
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("dst3.txt")).unwrap(), "3");
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("dst2.txt")).unwrap(), "2");
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("dst1.txt")).unwrap(), "1");
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("src.txt")).unwrap(), "0");
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn redirects_file_to_owned_fd() {
+        use std::os::fd::OwnedFd;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let dst: OwnedFd = File::create(tempdir.path().join("dst.txt")).unwrap().into();
+
+        // Act
+        src.redirect(&dst).unwrap();
+        drop(dst);
+        src.write_all(b"abc").unwrap();
+
+        // Assert
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("dst.txt")).unwrap(), "abc");
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn redirects_file_to_borrowed_fd() {
+        use std::os::fd::AsFd;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let dst = File::create(tempdir.path().join("dst.txt")).unwrap();
+
+        // Act
+        src.redirect(&dst.as_fd()).unwrap();
+        src.write_all(b"abc").unwrap();
+
+        // Assert
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("dst.txt")).unwrap(), "abc");
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn errors_on_redirect_to_closed_owned_fd() {
+        use std::os::fd::{AsRawFd, OwnedFd};
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let dst: OwnedFd = File::create(tempdir.path().join("dst.txt")).unwrap().into();
+
+        let dst = ManuallyDrop::new(dst);
+        unsafe { close(dst.as_raw_fd()) };
+
+        // Act
+        let err = src.redirect(&*dst).unwrap_err();
+
+        // Assert
+        assert_eq!(err.raw_os_error(), Some(libc::EBADF));
+    }
+
+    #[cfg(all(feature = "std", any(all(unix, feature = "libc_on_unix"), all(windows, feature = "libc_on_windows"))))]
+    #[test]
+    fn redirects_file_to_path_buf_and_str() {
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let path_buf: std::path::PathBuf = tempdir.path().join("dst1.txt");
+        let string = tempdir.path().join("dst2.txt").to_str().unwrap().to_string();
+
+        // Act
+        src.redirect(&*path_buf).unwrap();
+        src.write_all(b"abc").unwrap();
+        src.redirect(string.as_str()).unwrap();
+        src.write_all(b"def").unwrap();
+
+        // Assert
+        assert_eq!(std::fs::read_to_string(&path_buf).unwrap(), "abc");
+        assert_eq!(std::fs::read_to_string(&string).unwrap(), "def");
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn clones_descriptor_referring_to_same_file() {
+        use std::os::fd::AsRawFd;
+        use std::os::unix::fs::MetadataExt;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut original = File::create(tempdir.path().join("file.txt")).unwrap();
+
+        // Act
+        let cloned = clone_descriptor(&original).unwrap();
+        let cloned_cloexec = clone_descriptor_cloexec(&original).unwrap();
+        let mut cloned_file = File::from(cloned.try_clone().unwrap());
+        original.write_all(b"abc").unwrap();
+        cloned_file.write_all(b"def").unwrap();
+
+        // Assert
+        assert_ne!(cloned.as_raw_fd(), original.as_raw_fd());
+        assert_ne!(cloned_cloexec.as_raw_fd(), original.as_raw_fd());
+        assert_eq!(File::from(cloned).metadata().unwrap().ino(), original.metadata().unwrap().ino());
+        assert_eq!(unsafe { libc::fcntl(cloned_cloexec.as_raw_fd(), libc::F_GETFD) } & libc::FD_CLOEXEC, libc::FD_CLOEXEC);
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("file.txt")).unwrap(), "abcdef");
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn restores_original_destination_after_scoped_redirect() {
+        use std::os::fd::{AsRawFd, FromRawFd};
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let dst = File::create(tempdir.path().join("dst.txt")).unwrap();
+        let mut writer = ManuallyDrop::new(unsafe { File::from_raw_fd(src.as_raw_fd()) });
+
+        // Act
+        let result = scoped_redirect(&mut src, &dst, || writer.write_all(b"abc")).unwrap();
+        src.write_all(b"def").unwrap();
+
+        // Assert
+        result.unwrap();
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("dst.txt")).unwrap(), "abc");
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("src.txt")).unwrap(), "def");
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn restores_original_destination_when_scoped_closure_panics() {
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let dst = File::create(tempdir.path().join("dst.txt")).unwrap();
+
+        // Act
+        let panicked = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            scoped_redirect(&mut src, &dst, || panic!("boom"))
+        }));
+        src.write_all(b"abc").unwrap();
+
+        // Assert
+        assert!(panicked.is_err());
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("dst.txt")).unwrap(), "");
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("src.txt")).unwrap(), "abc");
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn errors_with_fds_on_fallible_redirect_to_closed_fd() {
+        use std::os::fd::AsRawFd;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let dst = ManuallyDrop::new(File::create(tempdir.path().join("dst.txt")).unwrap());
+        unsafe { close(dst.as_raw_fd()) };
+
+        // Act
+        let err = src.try_redirect(&*dst).unwrap_err();
+
+        // Assert
+        match &err {
+            RedirectError::DupFailed { src_fd, dst_fd, source } => {
+                assert_eq!(*src_fd, src.as_raw_fd());
+                assert_eq!(*dst_fd, dst.as_raw_fd());
+                assert_eq!(source.raw_os_error(), Some(libc::EBADF));
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+        assert_eq!(io::Error::from(err).raw_os_error(), None);
+    }
+
+    #[cfg(all(feature = "std", any(all(unix, feature = "libc_on_unix"), all(windows, feature = "libc_on_windows"))))]
+    #[test]
+    fn errors_with_path_on_fallible_redirect_with_missing_parent_directory() {
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let bad_path = tempdir.path().join("no_such_dir").join("f.txt");
+
+        // Act
+        let err = src.try_redirect(bad_path.as_path()).unwrap_err();
+
+        // Assert
+        match &err {
+            RedirectError::OpenDestinationFailed { path, source } => {
+                assert_eq!(path, &bad_path);
+                assert_eq!(source.kind(), io::ErrorKind::NotFound);
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+        assert_eq!(io::Error::from(err).kind(), io::ErrorKind::NotFound);
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn errors_on_redirect_to_same_fd() {
+        use std::os::fd::AsRawFd;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let same = FdRef::try_from_raw_fd(src.as_raw_fd()).unwrap();
+
+        // Act
+        let err = src.redirect(&same).unwrap_err();
+
+        // Assert
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn rolls_back_redirect_context_when_a_redirection_fails() {
+        use std::os::fd::AsRawFd;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut src1 = File::create(tempdir.path().join("src1.txt")).unwrap();
+        let mut src2 = File::create(tempdir.path().join("src2.txt")).unwrap();
+        let src3 = File::create(tempdir.path().join("src3.txt")).unwrap();
+        let dst = File::create(tempdir.path().join("dst.txt")).unwrap();
+        let unopened = i32::MAX;
+
+        // Act
+        let err = RedirectContext::new()
+            .add(src1.as_raw_fd(), dst.as_raw_fd())
+            .add(src2.as_raw_fd(), dst.as_raw_fd())
+            .add(src3.as_raw_fd(), unopened)
+            .apply()
+            .err()
+            .unwrap();
+        src1.write_all(b"1").unwrap();
+        src2.write_all(b"2").unwrap();
+
+        // Assert
+        assert_eq!(err.raw_os_error(), Some(libc::EBADF));
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("dst.txt")).unwrap(), "");
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("src1.txt")).unwrap(), "1");
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("src2.txt")).unwrap(), "2");
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn restores_redirect_context_when_dropped() {
+        use std::os::fd::AsRawFd;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut src1 = File::create(tempdir.path().join("src1.txt")).unwrap();
+        let mut src2 = File::create(tempdir.path().join("src2.txt")).unwrap();
+        let dst = File::create(tempdir.path().join("dst.txt")).unwrap();
+
+        // Act
+        let applied = RedirectContext::new()
+            .add(src1.as_raw_fd(), dst.as_raw_fd())
+            .add(src2.as_raw_fd(), dst.as_raw_fd())
+            .apply()
+            .unwrap();
+        src1.write_all(b"a").unwrap();
+        src2.write_all(b"b").unwrap();
+        drop(applied);
+        src1.write_all(b"1").unwrap();
+        src2.write_all(b"2").unwrap();
+
+        // Assert
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("dst.txt")).unwrap(), "ab");
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("src1.txt")).unwrap(), "1");
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("src2.txt")).unwrap(), "2");
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn opens_redirect_destination_without_cloexec_when_disabled() {
+        use std::os::fd::AsRawFd;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let opts = RedirectOptions { cloexec_intermediate: false, ..Default::default() };
+
+        // Act
+        let with_cloexec = RedirectOptions::default().open(&tempdir.path().join("a.txt")).unwrap();
+        let without_cloexec = opts.open(&tempdir.path().join("b.txt")).unwrap();
+
+        // Assert
+        assert_eq!(unsafe { libc::fcntl(with_cloexec.as_raw_fd(), libc::F_GETFD) } & libc::FD_CLOEXEC, libc::FD_CLOEXEC);
+        assert_eq!(unsafe { libc::fcntl(without_cloexec.as_raw_fd(), libc::F_GETFD) } & libc::FD_CLOEXEC, 0);
+    }
+
+    #[cfg(all(feature = "std", unix, feature = "libc_on_unix"))]
+    #[test]
+    fn opens_stdin_only_destination_read_only() {
+        use std::io::{Read, Write};
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().join("input.txt");
+        std::fs::write(&path, "input").unwrap();
+        let opts = RedirectOptions { include_stdin: true, include_stdout: false, include_stderr: false, ..Default::default() };
+
+        // Act
+        let mut file = opts.open(&path).unwrap();
+        let mut contents = String::new();
+        file.read_to_string(&mut contents).unwrap();
+        let written = file.write_all(b"output");
+
+        // Assert
+        assert_eq!(contents, "input");
+        assert!(written.is_err());
+        assert_eq!(opts.open_flags() & libc::O_ACCMODE, libc::O_RDONLY);
+        assert_eq!(RedirectOptions { include_stdin: true, ..Default::default() }.open_flags() & libc::O_ACCMODE, libc::O_RDWR);
+        assert_eq!(RedirectOptions::default().open_flags() & libc::O_ACCMODE, libc::O_WRONLY);
+    }
+
+    #[cfg(all(feature = "std", target_os = "linux", feature = "libc_on_unix"))]
+    #[test]
+    fn does_not_leak_redirect_destination_into_children() {
+        use std::os::fd::AsRawFd;
+        use std::process::Command;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let destination = RedirectOptions::default().open(&tempdir.path().join("dst.txt")).unwrap();
+        let mut src = tempfile::tempfile().unwrap();
+        src.redirect(tempdir.path().join("src.txt").as_path()).unwrap();
+        let is_inherited = |fd: i32| Command::new("/bin/sh").arg("-c").arg(format!("test -e /proc/$$/fd/{fd}")).status().unwrap().success();
+
+        // Act
+        let destination_inherited = is_inherited(destination.as_raw_fd());
+        let source_inherited = is_inherited(src.as_raw_fd());
+
+        // Assert
+        assert!(!destination_inherited);
+        assert!(source_inherited);
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix", feature = "tokio"))]
+    #[tokio::test]
+    async fn redirects_file_to_file_and_path_asynchronously() {
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let dst = File::create(tempdir.path().join("dst1.txt")).unwrap();
+        let dst_path = tempdir.path().join("dst2.txt");
+
+        // Act
+        src.redirect_async(&dst).await.unwrap();
+        src.write_all(b"abc").unwrap();
+        src.redirect_async(dst_path.as_path()).await.unwrap();
+        src.write_all(b"def").unwrap();
+
+        // Assert
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("dst1.txt")).unwrap(), "abc");
+        assert_eq!(std::fs::read_to_string(&dst_path).unwrap(), "def");
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix", feature = "tokio"))]
+    #[test]
+    fn redirects_file_to_tokio_file_outside_of_runtime() {
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let dst = tokio::fs::File::from_std(File::create(tempdir.path().join("dst.txt")).unwrap());
+
+        // Act
+        src.redirect(&dst).unwrap();
+        drop(dst);
+        src.write_all(b"abc").unwrap();
+
+        // Assert
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("dst.txt")).unwrap(), "abc");
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn redirects_file_to_name_relative_to_directory_fd() {
+        use std::os::fd::AsRawFd;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let dir = File::open(tempdir.path()).unwrap();
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+
+        // Act
+        unix::redirect_to_path_relative_to(&mut src, &unix::DirFd(dir.as_raw_fd()), std::ffi::OsStr::new("dst.txt"), &RedirectOptions::default()).unwrap();
+        src.write_all(b"abc").unwrap();
+
+        // Assert
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("dst.txt")).unwrap(), "abc");
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("src.txt")).unwrap(), "");
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn saves_descriptors_with_cloexec() {
+        use std::os::fd::AsRawFd;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let file = File::create(tempdir.path().join("file.txt")).unwrap();
+        let dst_path = tempdir.path().join("dst.txt");
+
+        // Act
+        let saved = dup_cloexec(file.as_raw_fd()).unwrap();
+        let opened = RedirectOptions::default().open(&dst_path).unwrap();
+
+        // Assert
+        assert_eq!(unsafe { libc::fcntl(saved.as_raw_fd(), libc::F_GETFD) } & libc::FD_CLOEXEC, libc::FD_CLOEXEC);
+        assert_eq!(unsafe { libc::fcntl(opened.as_raw_fd(), libc::F_GETFD) } & libc::FD_CLOEXEC, libc::FD_CLOEXEC);
+    }
+
+    #[cfg(all(target_os = "linux", feature = "linux-splice"))]
+    #[test]
+    fn splices_all_bytes_between_pipes() {
+        use std::os::fd::AsRawFd;
+        // Arrange
+        let (src_read, src_write) = pipes::pipe_cloexec().unwrap();
+        let (dst_read, dst_write) = pipes::pipe_cloexec().unwrap();
+        let payload = vec![7u8; 200 * 1024];
+        let expected = payload.clone();
+        let writer = std::thread::spawn(move || File::from(src_write).write_all(&payload));
+        let reader = std::thread::spawn(move || {
+            let mut received = Vec::new();
+            File::from(dst_read).read_to_end(&mut received).map(|_| received)
+        });
+
+        // Act
+        let moved = unix::splice_loop(src_read.as_raw_fd(), dst_write.as_raw_fd()).unwrap();
+        drop(dst_write);
+
+        // Assert
+        writer.join().unwrap().unwrap();
+        assert_eq!(moved, expected.len() as u64);
+        assert_eq!(reader.join().unwrap().unwrap(), expected);
+    }
+
+    #[cfg(all(feature = "std", unix, feature = "libc_on_unix"))]
+    #[test]
+    fn reports_current_redirect_of_fd() {
+        use std::os::fd::AsRawFd;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let file_path = tempdir.path().join("file.txt");
+        let mut src = File::create(&file_path).unwrap();
+        let (read_end, _write_end) = pipes::pipe_cloexec().unwrap();
+
+        // Act
+        let as_file = unix::current_redirect(src.as_raw_fd()).unwrap();
+        let as_pipe = unix::current_redirect(read_end.as_raw_fd()).unwrap();
+        redirect_to_null(&mut src).unwrap();
+        let as_device = unix::current_redirect(src.as_raw_fd()).unwrap();
+
+        // Assert
+        assert_eq!(as_file, unix::RedirectKind::File(file_path.canonicalize().unwrap()));
+        assert_eq!(as_pipe, unix::RedirectKind::Pipe);
+        assert_eq!(as_device, unix::RedirectKind::Device);
+        assert_eq!(as_device.to_string(), "device");
+        assert!(unix::current_redirect(-1).is_err());
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn redirects_only_when_condition_holds() {
+        use std::io::{Read, Write};
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let src_path = tempdir.path().join("src.txt");
+        let dst_path = tempdir.path().join("dst.txt");
+        let mut src = File::create(&src_path).unwrap();
+        let dst = File::create(&dst_path).unwrap();
+
+        // Act
+        redirect_conditional(&mut src, &dst, false).unwrap();
+        src.write_all(b"kept").unwrap();
+        src.redirect_if(&dst, true).unwrap();
+        src.write_all(b"moved").unwrap();
+
+        // Assert
+        let mut src_contents = String::new();
+        File::open(&src_path).unwrap().read_to_string(&mut src_contents).unwrap();
+        let mut dst_contents = String::new();
+        File::open(&dst_path).unwrap().read_to_string(&mut dst_contents).unwrap();
+        assert_eq!(src_contents, "kept");
+        assert_eq!(dst_contents, "moved");
+    }
+
+    #[cfg(all(feature = "std", unix, feature = "libc_on_unix", feature = "log"))]
+    #[test]
+    fn logs_redirected_fds() {
+        use std::os::fd::AsRawFd;
+        use std::sync::Mutex;
+
+        struct RecordingLogger(Mutex<Vec<String>>);
+        impl log::Log for RecordingLogger {
+            fn enabled(&self, _: &log::Metadata) -> bool {
+                return true;
+            }
+            fn log(&self, record: &log::Record) {
+                self.0.lock().unwrap().push(format!("{}: {}", record.target(), record.args()));
+            }
+            fn flush(&self) {}
+        }
+        static LOGGER: RecordingLogger = RecordingLogger(Mutex::new(Vec::new()));
+
+        // Arrange
+        log::set_logger(&LOGGER).unwrap();
+        log::set_max_level(log::LevelFilter::Trace);
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let dst_path = tempdir.path().join("dst.txt");
+
+        // Act
+        src.redirect(dst_path.as_path()).unwrap();
+
+        // Assert
+        let records = LOGGER.0.lock().unwrap();
+        assert!(records.contains(&format!("io_redirect::options: opening {:?} for redirect", dst_path)));
+        assert!(records.iter().any(|r| r.starts_with(&format!("io_redirect::libc_common: redirect fd {} → fd ", src.as_raw_fd()))));
+    }
+
+    #[cfg(all(feature = "std", unix, feature = "libc_on_unix"))]
+    #[test]
+    fn redirects_to_path_with_mode() {
+        use std::io::Write;
+        use std::os::unix::fs::PermissionsExt;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let dst_path = tempdir.path().join("secret.log");
+
+        // Act
+        redirect_to_path_with_mode(&mut src, &dst_path, &RedirectOptions::default(), 0o600).unwrap();
+        src.write_all(b"secret").unwrap();
+
+        // Assert
+        let metadata = std::fs::metadata(&dst_path).unwrap();
+        assert_eq!(metadata.permissions().mode() & 0o777, 0o600);
+        assert_eq!(std::fs::read_to_string(&dst_path).unwrap(), "secret");
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn errors_on_invalid_builder_target_without_redirecting() {
+        use std::os::fd::AsRawFd;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let stdout_path = tempdir.path().join("out.txt");
+        let stdout_before = unix::current_redirect(std::io::stdout().as_raw_fd()).unwrap();
+
+        // Act
+        let result = RedirectBuilder::new().stdout_to(stdout_path.as_path()).stderr_to(RedirectTarget::Fd(-1)).apply();
+
+        // Assert
+        assert_eq!(result.err().unwrap().kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(unix::current_redirect(std::io::stdout().as_raw_fd()).unwrap(), stdout_before);
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn verifies_redirect_by_file_identity() {
+        use std::os::fd::AsRawFd;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let dst = File::create(tempdir.path().join("dst.txt")).unwrap();
+        let (read_end, write_end) = pipes::pipe_cloexec().unwrap();
+
+        // Act
+        let before = unix::verify_redirect(src.as_raw_fd(), dst.as_raw_fd()).unwrap();
+        src.redirect(&dst).unwrap();
+        let after = unix::verify_redirect(src.as_raw_fd(), dst.as_raw_fd()).unwrap();
+        let pipe = unix::verify_redirect(read_end.as_raw_fd(), write_end.as_raw_fd()).unwrap();
+
+        // Assert
+        assert!(!before);
+        assert!(after);
+        assert!(!pipe);
+        assert!(unix::verify_redirect(-1, dst.as_raw_fd()).is_err());
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn reads_pipe_redirect_incrementally() {
+        use std::os::fd::AsRawFd;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let src_path = tempdir.path().join("src.txt");
+        let mut src = File::create(&src_path).unwrap();
+        let redirect = pipe_redirect(src.as_raw_fd()).unwrap();
+
+        // Act
+        src.write_all(b"first").unwrap();
+        let mut first = [0u8; 5];
+        redirect.read_end().read_exact(&mut first).unwrap();
+        src.write_all(b"second").unwrap();
+        let mut second = [0u8; 6];
+        redirect.read_end().read_exact(&mut second).unwrap();
+        drop(redirect);
+        src.write_all(b"restored").unwrap();
+
+        // Assert
+        assert_eq!(&first, b"first");
+        assert_eq!(&second, b"second");
+        assert_eq!(std::fs::read_to_string(&src_path).unwrap(), "restored");
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn reads_pipe_redirect_at_once() {
+        use std::os::fd::AsRawFd;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let src_path = tempdir.path().join("src.txt");
+        let mut src = File::create(&src_path).unwrap();
+        let redirect = pipe_redirect(src.as_raw_fd()).unwrap();
+
+        // Act
+        src.write_all(b"first ").unwrap();
+        src.write_all(b"second").unwrap();
+        let captured = redirect.finish().unwrap();
+        src.write_all(b"restored").unwrap();
+
+        // Assert
+        assert_eq!(captured, b"first second");
+        assert_eq!(std::fs::read_to_string(&src_path).unwrap(), "restored");
+    }
+
+    #[cfg(all(windows, feature = "windows-sys"))]
+    #[test]
+    fn redirects_owned_handle_to_file() {
+        use std::os::windows::io::OwnedHandle;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut src: OwnedHandle = File::create(tempdir.path().join("src.txt")).unwrap().into();
+        let dst = File::create(tempdir.path().join("dst.txt")).unwrap();
+
+        // Act
+        src.redirect(&dst).unwrap();
+        File::from(src).write_all(b"Hello!").unwrap();
+
+        // Assert
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("dst.txt")).unwrap(), "Hello!");
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("src.txt")).unwrap(), "");
+    }
+
+    #[cfg(all(windows, feature = "windows-sys", not(feature = "libc_on_windows")))]
+    #[test]
+    fn redirects_file_to_file_with_duplicate_handle() {
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let dst = File::create(tempdir.path().join("dst.txt")).unwrap();
+
+        // Act
+        src.redirect(&dst).unwrap();
+        drop(dst);
+        src.write_all(b"Hello!").unwrap();
+
+        // Assert
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("dst.txt")).unwrap(), "Hello!");
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("src.txt")).unwrap(), "");
+    }
+
+    #[cfg(all(feature = "std", unix, feature = "libc_on_unix"))]
+    #[test]
+    fn redirects_only_non_tty_sources() {
+        use std::os::fd::FromRawFd;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let dst_path = tempdir.path().join("dst.txt");
+        let mut file = File::create(tempdir.path().join("src.txt")).unwrap();
+        let tty_fd = unsafe { libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY) };
+        assert!(tty_fd >= 0, "{}", io::Error::last_os_error());
+        let mut tty = unsafe { File::from_raw_fd(tty_fd) };
+
+        // Act
+        let file_redirected = redirect_if_not_tty(&mut file, dst_path.as_path()).unwrap();
+        let tty_redirected = redirect_if_not_tty(&mut tty, dst_path.as_path()).unwrap();
+        file.write_all(b"Hello!").unwrap();
+
+        // Assert
+        assert!(file_redirected);
+        assert!(!tty_redirected);
+        assert_eq!(unsafe { libc::isatty(tty_fd) }, 1);
+        assert_eq!(std::fs::read_to_string(&dst_path).unwrap(), "Hello!");
+    }
+
+    #[cfg(all(target_os = "linux", feature = "linux-pidfd"))]
+    #[test]
+    fn borrows_fd_from_process() {
+        use std::os::fd::AsRawFd;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let dst_path = tempdir.path().join("dst.txt");
+        let dst = File::create(&dst_path).unwrap();
+
+        // Act
+        let borrowed = unix::borrow_fd_from_process(std::process::id(), dst.as_raw_fd() as u32).unwrap();
+        File::from(borrowed).write_all(b"Hello!").unwrap();
+
+        // Assert
+        assert_eq!(std::fs::read_to_string(&dst_path).unwrap(), "Hello!");
+    }
+
+    #[cfg(all(feature = "std", any(all(unix, feature = "libc_on_unix"), all(windows, feature = "libc_on_windows"))))]
+    #[test]
+    fn redirects_file_to_path_creating_parents() {
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let dst_path = tempdir.path().join("first").join("second").join("dst.txt");
+
+        // Act
+        redirect_to_path_create_parents(&mut src, &dst_path, &RedirectOptions::default()).unwrap();
+        src.write_all(b"Hello!").unwrap();
+
+        // Assert
+        assert_eq!(std::fs::read_to_string(&dst_path).unwrap(), "Hello!");
+    }
+
+    #[cfg(all(any(target_os = "freebsd", target_os = "netbsd", target_os = "openbsd"), feature = "libc_on_unix"))]
+    #[test]
+    fn errors_with_ebadf_on_redirect_to_closed_fd_on_bsd() {
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let closed = unsafe { FdRef::from_raw_fd_unchecked(i32::MAX) };
+
+        // Act
+        let err = src.redirect(&closed).unwrap_err();
+
+        // Assert
+        assert_eq!(err.raw_os_error(), Some(libc::EBADF));
+    }
+
+    #[cfg(all(feature = "std", any(target_os = "freebsd", target_os = "netbsd", target_os = "openbsd"), feature = "libc_on_unix"))]
+    #[test]
+    fn clears_cloexec_on_redirected_fd_on_bsd() {
+        use std::os::fd::AsRawFd;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let dst_path = tempdir.path().join("dst.txt");
+        assert_ne!(unsafe { libc::fcntl(src.as_raw_fd(), libc::F_GETFD) } & libc::FD_CLOEXEC, 0);
+
+        // Act
+        src.redirect(dst_path.as_path()).unwrap();
+        src.write_all(b"Hello!").unwrap();
+
+        // Assert
+        assert_eq!(unsafe { libc::fcntl(src.as_raw_fd(), libc::F_GETFD) } & libc::FD_CLOEXEC, 0);
+        assert_eq!(std::fs::read_to_string(&dst_path).unwrap(), "Hello!");
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix", feature = "scm-rights"))]
+    #[test]
+    fn redirects_to_fd_received_over_socket() {
+        use std::os::unix::net::UnixStream;
+        use std::os::fd::AsRawFd;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let dst_path = tempdir.path().join("dst.txt");
+        let dst = File::create(&dst_path).unwrap();
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let (sender, receiver) = UnixStream::pair().unwrap();
+
+        // Act
+        unix::send_fd(&sender, dst.as_raw_fd()).unwrap();
+        drop(dst);
+        let received = unix::recv_fd(&receiver).unwrap();
+        src.redirect(&received).unwrap();
+        src.write_all(b"Hello!").unwrap();
+
+        // Assert
+        assert_ne!(unsafe { libc::fcntl(received.as_raw_fd(), libc::F_GETFD) } & libc::FD_CLOEXEC, 0);
+        assert_eq!(std::fs::read_to_string(&dst_path).unwrap(), "Hello!");
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix", feature = "scm-rights"))]
+    #[test]
+    fn rejects_message_without_fd() {
+        use std::os::unix::net::UnixStream;
+        // Arrange
+        let (mut sender, receiver) = UnixStream::pair().unwrap();
+        sender.write_all(b"x").unwrap();
+
+        // Act
+        let received = unix::recv_fd(&receiver);
+
+        // Assert
+        assert_eq!(received.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[cfg(all(target_os = "linux", feature = "systemd"))]
+    #[test]
+    fn errors_on_missing_journald_socket_without_redirecting() {
+        use std::os::fd::AsRawFd;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let stdout_before = unix::current_redirect(std::io::stdout().as_raw_fd()).unwrap();
+
+        // Act
+        let result = redirect_std_to_journald_socket(tempdir.path().join("missing.sock"));
+
+        // Assert
+        assert_eq!(result.err().unwrap().kind(), io::ErrorKind::NotFound);
+        assert_eq!(unix::current_redirect(std::io::stdout().as_raw_fd()).unwrap(), stdout_before);
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn reopens_redirect_after_rotation() {
+        use std::os::fd::AsRawFd;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let log_path = tempdir.path().join("app.log");
+        let rotated_path = tempdir.path().join("app.log.1");
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let mut handle = unix::redirect_to_path(&mut src, &log_path, &RedirectOptions::default()).unwrap();
+
+        // Act
+        src.write_all(b"before").unwrap();
+        std::fs::rename(&log_path, &rotated_path).unwrap();
+        unix::reopen_redirect(&mut handle, &log_path, &RedirectOptions::default()).unwrap();
+        src.write_all(b"after").unwrap();
+
+        // Assert
+        assert_eq!(handle.src_fd(), src.as_raw_fd());
+        assert_eq!(std::fs::read_to_string(&rotated_path).unwrap(), "before");
+        assert_eq!(std::fs::read_to_string(&log_path).unwrap(), "after");
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn redirects_fd_to_memory() {
+        use std::os::fd::AsRawFd;
         // Arrange
         let tempdir = tempfile::tempdir().unwrap();
-        let mut src_file = File::create(tempdir.path().join("src.txt")).unwrap();
-        let dst_file = File::create(tempdir.path().join("dst.txt")).unwrap();
+        let src_path = tempdir.path().join("src.txt");
+        let mut src = File::create(&src_path).unwrap();
+
+        // Act
+        let capture = redirect_to_cursor(src.as_raw_fd()).unwrap();
+        src.write_all(b"first ").unwrap();
+        let partial = capture.get();
+        src.write_all(b"second").unwrap();
+        let captured = capture.finish().unwrap();
+        src.write_all(b"restored").unwrap();
+
+        // Assert
+        assert!(b"first ".starts_with(&partial));
+        assert_eq!(captured, b"first second");
+        assert_eq!(std::fs::read_to_string(&src_path).unwrap(), "restored");
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn redirects_through_trait_objects() {
+        use std::os::fd::AsRawFd;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let first: Box<dyn AsRawFd> = Box::new(File::create(tempdir.path().join("first.txt")).unwrap());
+        let second: Box<dyn AsRawFd> = Box::new(File::create(tempdir.path().join("second.txt")).unwrap());
+
+        // Act
+        redirect_to_dyn(src.as_raw_fd(), first.as_ref()).unwrap();
+        src.write_all(b"first").unwrap();
+        redirect_from_dyn(&mut src, second.as_ref()).unwrap();
+        src.write_all(b"second").unwrap();
+
+        // Assert
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("first.txt")).unwrap(), "first");
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("second.txt")).unwrap(), "second");
+    }
 
-        let dst_file = ManuallyDrop::new(dst_file);
-        let fd = dst_file.as_raw_fd();
-        unsafe { close(fd) };
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn closes_dup_fd_on_drop() {
+        use std::io::Read;
+        // Arrange
+        let (mut read_end, write_end) = io::pipe().unwrap();
+        let write_end = DupFd::from(std::os::fd::OwnedFd::from(write_end));
+        let mut buf = Vec::new();
 
         // Act
-        let err = src_file.redirect(&*dst_file).unwrap_err();
+        drop(write_end);
+        let count = read_end.read_to_end(&mut buf).unwrap();
 
         // Assert
-        assert!(err.raw_os_error().is_some());
+        assert_eq!(count, 0);
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn drops_closed_dup_fd_without_panicking() {
+        // Arrange
+        let invalid = unsafe { DupFd::new(-1) };
+
+        // Act
+        drop(invalid);
+
+        // Assert
+        assert_eq!(io::Error::last_os_error().raw_os_error(), Some(libc::EBADF));
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn flushes_buffered_writer_before_redirect() {
+        use std::io::BufWriter;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut writer = BufWriter::new(File::create(tempdir.path().join("first.txt")).unwrap());
+        let second = File::create(tempdir.path().join("second.txt")).unwrap();
+        writer.write_all(b"buffered").unwrap();
+
+        // Act
+        redirect_buffered(&mut writer, &second).unwrap();
+        writer.write_all(b"redirected").unwrap();
+        writer.flush().unwrap();
+
+        // Assert
+        assert!(writer.buffer().is_empty());
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("first.txt")).unwrap(), "buffered");
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("second.txt")).unwrap(), "redirected");
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn redirects_with_dup2_before_falling_back() {
+        use std::os::fd::AsRawFd;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let dest = File::create(tempdir.path().join("dst.txt")).unwrap();
+
+        // Act
+        let handle = redirect_with_fallback(src.as_raw_fd(), dest).unwrap();
+        let is_fallback = handle.is_fallback();
+        src.write_all(b"redirected").unwrap();
+        handle.finish().unwrap();
+        src.write_all(b"restored").unwrap();
+
+        // Assert
+        assert!(!is_fallback);
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("dst.txt")).unwrap(), "redirected");
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("src.txt")).unwrap(), "restored");
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn copies_through_pipe_when_falling_back() {
+        use std::os::fd::AsRawFd;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let dest = std::io::BufWriter::new(File::create(tempdir.path().join("dst.txt")).unwrap());
+
+        // Act
+        let handle = redirect_writer_with_fallback(src.as_raw_fd(), dest).unwrap();
+        let is_fallback = handle.is_fallback();
+        src.write_all(b"copied").unwrap();
+        handle.finish().unwrap();
+        src.write_all(b"restored").unwrap();
+
+        // Assert
+        assert!(is_fallback);
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("dst.txt")).unwrap(), "copied");
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("src.txt")).unwrap(), "restored");
+    }
+
+    #[cfg(all(target_os = "linux", feature = "libc_on_unix"))]
+    #[test]
+    fn publishes_tmpfile_after_redirect() {
+        use std::os::fd::AsRawFd;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let dir = File::open(tempdir.path()).unwrap();
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let tmpfile = unix::create_tmpfile(tempdir.path(), 0o644).unwrap();
+
+        // Act
+        src.redirect(&tmpfile).unwrap();
+        src.write_all(b"published").unwrap();
+        let visible_before = std::fs::read_dir(tempdir.path()).unwrap().count();
+        unix::link_tmpfile(&tmpfile, dir.as_raw_fd(), "published.txt".as_ref()).unwrap();
+
+        // Assert
+        assert_eq!(visible_before, 1);
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("published.txt")).unwrap(), "published");
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn formats_errors_and_guards() {
+        use std::os::fd::AsRawFd;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let dst = File::create(tempdir.path().join("dst.txt")).unwrap();
+        let error = RedirectError::DupFailed { src_fd: 1, dst_fd: 5, source: io::Error::from_raw_os_error(libc::EBADF) };
+
+        // Act
+        let guard = src.save_and_redirect(&dst).unwrap();
+        let debug = format!("{guard:?}");
+        let display = error.to_string();
+
+        // Assert
+        assert!(debug.starts_with("RedirectGuard"));
+        assert!(debug.contains(&src.as_raw_fd().to_string()));
+        assert!(display.starts_with("redirect fd 1 → fd 5 failed: "));
+        assert_eq!(RedirectOptions::default(), RedirectOptions::default().clone());
+    }
+
+    #[cfg(all(feature = "std", unix, feature = "libc_on_unix"))]
+    #[test]
+    fn checks_whether_redirect_to_path_is_active() {
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let dst_path = tempdir.path().join("dst.txt");
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let (read_end, _write_end) = io::pipe().unwrap();
+
+        // Act
+        let before = is_redirect_active(&src, &dst_path);
+        src.redirect(dst_path.as_path()).unwrap();
+        let after = is_redirect_active(&src, &dst_path).unwrap();
+        let pipe = is_redirect_active(&read_end, &dst_path).unwrap();
+
+        // Assert
+        assert_eq!(before.unwrap_err().kind(), io::ErrorKind::NotFound);
+        assert!(after);
+        assert!(!pipe);
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn restores_redirect_after_duration() {
+        use std::os::fd::AsRawFd;
+        use std::time::{Duration, Instant};
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let dst = File::create(tempdir.path().join("dst.txt")).unwrap();
+        let started = Instant::now();
+
+        // Act
+        let handle = redirect_for_duration(src.as_raw_fd(), dst.as_raw_fd(), Duration::from_millis(100)).unwrap();
+        src.write_all(b"suppressed").unwrap();
+        drop(handle);
+        src.write_all(b"restored").unwrap();
+
+        // Assert
+        assert!(started.elapsed() >= Duration::from_millis(100));
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("dst.txt")).unwrap(), "suppressed");
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("src.txt")).unwrap(), "restored");
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn cancels_timed_redirect_early() {
+        use std::os::fd::AsRawFd;
+        use std::time::{Duration, Instant};
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let dst = File::create(tempdir.path().join("dst.txt")).unwrap();
+        let started = Instant::now();
+
+        // Act
+        let handle = redirect_for_duration(src.as_raw_fd(), dst.as_raw_fd(), Duration::from_secs(60)).unwrap();
+        src.write_all(b"suppressed").unwrap();
+        handle.cancel_and_restore().unwrap();
+        src.write_all(b"restored").unwrap();
+
+        // Assert
+        assert!(started.elapsed() < Duration::from_secs(60));
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("dst.txt")).unwrap(), "suppressed");
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("src.txt")).unwrap(), "restored");
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn detects_platform_capabilities_once() {
+        // Arrange
+        let first = unix::detect_platform_capabilities();
+
+        // Act
+        let second = unix::detect_platform_capabilities();
+
+        // Assert
+        assert_eq!(first, second);
+        assert!(first.has_cloexec_dup);
+        assert_eq!(first.has_proc_fd, cfg!(target_os = "linux"));
+        assert_eq!(first.has_splice, cfg!(target_os = "linux"));
+    }
+
+    #[cfg(all(feature = "std", any(all(unix, feature = "libc_on_unix"), all(windows, feature = "libc_on_windows"))))]
+    #[test]
+    fn redirects_to_owned_path_types() {
+        use std::ffi::OsString;
+        use std::path::PathBuf;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let as_string: String = tempdir.path().join("string.txt").to_str().unwrap().to_string();
+        let as_path_buf: PathBuf = tempdir.path().join("path_buf.txt");
+        let as_os_string: OsString = tempdir.path().join("os_string.txt").into_os_string();
+
+        // Act
+        redirect_to_path_like(&mut src, &as_string).unwrap();
+        src.write_all(b"string").unwrap();
+        redirect_to_path_like(&mut src, &as_path_buf).unwrap();
+        src.write_all(b"path_buf").unwrap();
+        redirect_to_path_like(&mut src, &as_os_string).unwrap();
+        src.write_all(b"os_string").unwrap();
+
+        // Assert
+        assert_eq!(std::fs::read_to_string(&as_string).unwrap(), "string");
+        assert_eq!(std::fs::read_to_string(&as_path_buf).unwrap(), "path_buf");
+        assert_eq!(std::fs::read_to_string(&as_os_string).unwrap(), "os_string");
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn extends_redirect_with_shared_offset() {
+        use std::os::fd::AsRawFd;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let log_path = tempdir.path().join("log.txt");
+        let mut first = File::create(tempdir.path().join("first.txt")).unwrap();
+        let mut second = File::create(tempdir.path().join("second.txt")).unwrap();
+        first.redirect(&File::create(&log_path).unwrap()).unwrap();
+
+        // Act
+        unix::extend_redirect(first.as_raw_fd(), second.as_raw_fd()).unwrap();
+        for _ in 0..3 {
+            first.write_all(b"A").unwrap();
+            second.write_all(b"B").unwrap();
+        }
+
+        // Assert
+        assert_eq!(std::fs::read_to_string(&log_path).unwrap(), "ABABAB");
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn captures_fd_output_of_closure() {
+        use std::os::fd::AsRawFd;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let src_fd = src.as_raw_fd();
+
+        // Act
+        let (captured, result) = capture_fd_output(src_fd, || unsafe { libc::write(src_fd, b"captured".as_ptr().cast(), 8) }).unwrap();
+        src.write_all(b"restored").unwrap();
+
+        // Assert
+        assert_eq!(captured, b"captured");
+        assert_eq!(result, 8);
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("src.txt")).unwrap(), "restored");
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn restores_fd_when_capture_closure_panics() {
+        use std::os::fd::AsRawFd;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let src_fd = src.as_raw_fd();
+
+        // Act
+        let outcome = std::panic::catch_unwind(|| capture_fd_output(src_fd, || panic!("closure failed")));
+        src.write_all(b"restored").unwrap();
+
+        // Assert
+        assert!(outcome.is_err());
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("src.txt")).unwrap(), "restored");
+    }
+
+    #[cfg(all(target_os = "linux", feature = "libc_on_unix"))]
+    #[test]
+    fn materializes_tmpfile_through_proc() {
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let dir = File::open(tempdir.path()).unwrap();
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let tmpfile = unix::create_tmpfile(tempdir.path(), 0o644).unwrap();
+
+        // Act
+        src.redirect(&tmpfile).unwrap();
+        src.write_all(b"materialized").unwrap();
+        unix::link_fd_to_path(&tmpfile, &dir, "materialized.txt".as_ref()).unwrap();
+
+        // Assert
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("materialized.txt")).unwrap(), "materialized");
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn transfers_saved_fd_out_of_and_into_guard() {
+        use std::os::fd::AsRawFd;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let dst = File::create(tempdir.path().join("dst.txt")).unwrap();
+        let guard = src.save_and_redirect(&dst).unwrap();
+
+        // Act
+        let saved_fd = guard.into_saved_fd();
+        src.write_all(b"redirected").unwrap();
+        drop(unsafe { RedirectGuard::from_saved_fd(saved_fd, src.as_raw_fd()) });
+        src.write_all(b"restored").unwrap();
+
+        // Assert
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("dst.txt")).unwrap(), "redirected");
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("src.txt")).unwrap(), "restored");
+    }
+
+    #[cfg(any(all(unix, feature = "libc_on_unix"), all(windows, feature = "windows-sys")))]
+    #[test]
+    fn transfers_bytes_through_public_pipes() {
+        use std::io::Read;
+        // Arrange
+        let (inherited_read, inherited_write) = pipe().unwrap();
+        let (cloexec_read, cloexec_write) = pipe_cloexec().unwrap();
+        let mut received = [String::new(), String::new()];
+
+        // Act
+        File::from(inherited_write).write_all(b"inherited").unwrap();
+        File::from(cloexec_write).write_all(b"cloexec").unwrap();
+        File::from(inherited_read).read_to_string(&mut received[0]).unwrap();
+        File::from(cloexec_read).read_to_string(&mut received[1]).unwrap();
+
+        // Assert
+        assert_eq!(received, ["inherited", "cloexec"]);
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn sets_cloexec_only_on_pipe_cloexec() {
+        use std::os::fd::AsRawFd;
+        // Arrange
+        let (inherited_read, inherited_write) = pipe().unwrap();
+        let (cloexec_read, cloexec_write) = pipe_cloexec().unwrap();
+
+        // Act
+        let flags = [&inherited_read, &inherited_write, &cloexec_read, &cloexec_write].map(|fd| unsafe { libc::fcntl(fd.as_raw_fd(), libc::F_GETFD) } & libc::FD_CLOEXEC);
+
+        // Assert
+        assert_eq!(flags, [0, 0, libc::FD_CLOEXEC, libc::FD_CLOEXEC]);
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn rejects_nul_in_exec_arguments_before_redirecting() {
+        use std::os::fd::AsRawFd;
+        use std::path::Path;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+        let dst = File::create(tempdir.path().join("dst.txt")).unwrap();
+
+        // Act
+        let error = unix::redirect_and_exec(Path::new("/bin/echo"), &["hel\0lo".into()], &[], &[(src.as_raw_fd(), dst.as_raw_fd())]);
+        src.write_all(b"not redirected").unwrap();
+
+        // Assert
+        assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("src.txt")).unwrap(), "not redirected");
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn spawns_child_with_file_actions() {
+        use std::os::fd::AsRawFd;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let out_path = tempdir.path().join("out.txt");
+        let err_file = File::create(tempdir.path().join("err.txt")).unwrap();
+        let mut actions = unix::PosixSpawnActions::new().unwrap();
+        actions.add_open(libc::STDOUT_FILENO, &out_path, libc::O_WRONLY | libc::O_CREAT | libc::O_TRUNC, 0o644).unwrap();
+        actions.add_dup2(err_file.as_raw_fd(), libc::STDERR_FILENO).unwrap();
+        actions.add_close(libc::STDIN_FILENO).unwrap();
+
+        // Act
+        let pid = unix::spawn_with_actions(std::path::Path::new("/bin/sh"), &["-c".as_ref(), "echo out; echo err >&2".as_ref()], &actions).unwrap();
+        let mut status = 0;
+        let waited = unsafe { libc::waitpid(pid as libc::pid_t, &mut status, 0) };
+
+        // Assert
+        assert_eq!(waited, pid as libc::pid_t);
+        assert!(libc::WIFEXITED(status) && libc::WEXITSTATUS(status) == 0);
+        assert_eq!(std::fs::read_to_string(&out_path).unwrap(), "out\n");
+        assert_eq!(std::fs::read_to_string(tempdir.path().join("err.txt")).unwrap(), "err\n");
+    }
+
+    #[test]
+    fn chains_redirect_error_to_io_error() {
+        use std::error::Error;
+        use std::path::PathBuf;
+        // Arrange
+        let dup_failed = RedirectError::DupFailed { src_fd: 1, dst_fd: 5, source: io::Error::new(io::ErrorKind::InvalidInput, "bad fd") };
+        let open_failed = RedirectError::OpenDestinationFailed { path: PathBuf::from("missing/log.txt"), source: io::Error::new(io::ErrorKind::NotFound, "no such file") };
+
+        // Act
+        let dup_source = dup_failed.source().map(|source| source.to_string());
+        let open_source = open_failed.source().map(|source| source.to_string());
+        let dup_message = dup_failed.to_string();
+        let open_message = open_failed.to_string();
+        let converted = io::Error::from(open_failed);
+
+        // Assert
+        assert_eq!(dup_source.as_deref(), Some("bad fd"));
+        assert_eq!(open_source.as_deref(), Some("no such file"));
+        assert_eq!(dup_message, "redirect fd 1 → fd 5 failed: bad fd");
+        assert_eq!(open_message, "open \"missing/log.txt\" for redirect failed: no such file");
+        assert_eq!(converted.kind(), io::ErrorKind::NotFound);
+        assert!(converted.get_ref().unwrap().downcast_ref::<RedirectError>().is_some());
+    }
+
+    #[cfg(all(feature = "std", unix, feature = "libc_on_unix"))]
+    #[test]
+    fn rejects_second_locked_redirect_to_same_file() {
+        use std::io::Write;
+        // Arrange
+        let tempdir = tempfile::tempdir().unwrap();
+        let path = tempdir.path().join("dst.txt");
+        let mut first = tempfile::tempfile().unwrap();
+        let mut second = tempfile::tempfile().unwrap();
+        let lock = redirect_to_path_with_lock(&mut first, &path, false).unwrap();
+
+        // Act
+        let contended = redirect_to_path_with_lock(&mut second, &path, false);
+        first.write_all(b"first").unwrap();
+        drop(lock);
+        let released = redirect_to_path_with_lock(&mut second, &path, true);
+
+        // Assert
+        assert_eq!(contended.unwrap_err().kind(), std::io::ErrorKind::WouldBlock);
+        assert!(released.is_ok());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "first");
+    }
+
+    #[cfg(all(unix, feature = "libc_on_unix"))]
+    #[test]
+    fn redirects_fd_to_writer_and_returns_it() {
+        use std::io::Write;
+        use std::os::fd::AsRawFd;
+        // Arrange
+        let mut src = tempfile::tempfile().unwrap();
+
+        // Act
+        let redirect = redirect_to_writer(src.as_raw_fd(), Vec::new()).unwrap();
+        src.write_all(b"abc").unwrap();
+        src.write_all(b"def").unwrap();
+        let written = redirect.finish().unwrap();
+        src.write_all(b"ghi").unwrap();
+
+        // Assert
+        assert_eq!(written, b"abcdef");
+        assert_eq!(src.metadata().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn formats_partial_redirect_error() {
+        use std::error::Error;
+        // Arrange
+        let error = RedirectError::PartialRedirect { completed: vec!["stdin".to_string(), "stdout".to_string()], failed_at: "stderr".to_string(), source: io::Error::new(io::ErrorKind::InvalidInput, "bad fd") };
+
+        // Act
+        let message = error.to_string();
+        let source = error.source().map(|source| source.to_string());
+
+        // Assert
+        assert_eq!(message, "failed to redirect stderr after redirecting [stdin, stdout]: bad fd");
+        assert_eq!(source.as_deref(), Some("bad fd"));
     }
-}
\ No newline at end of file
+}
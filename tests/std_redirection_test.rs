@@ -1,5 +1,6 @@
+#![cfg(feature = "std")]
+
 use assert_cmd::prelude::*;
-use predicates::prelude::*;
 use std::process::Command;
 
 #[test]
@@ -7,4 +8,347 @@ fn runs_selftest() -> Result<(), Box<dyn std::error::Error>> {
     let mut cmd = Command::cargo_bin("examples/selftest")?;
     cmd.assert().success();
     Ok(())
+}
+
+#[test]
+fn runs_selftest_stdin_from_path() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("stdin_from_path").assert().success();
+    Ok(())
+}
+
+#[test]
+fn runs_selftest_all_std_to_path() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("all_std_to_path").assert().success();
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn runs_selftest_tee_stderr() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("tee_stderr").assert().success();
+    Ok(())
+}
+
+#[test]
+fn runs_selftest_std_to_null() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("std_to_null").assert().success();
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn runs_selftest_capture_std() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("capture_std").assert().success();
+    Ok(())
+}
+
+#[test]
+fn runs_selftest_stdin_to_file() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("stdin_to_file").assert().success();
+    Ok(())
+}
+
+#[test]
+fn runs_selftest_stdout_to_file() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("stdout_to_file").assert().success();
+    Ok(())
+}
+
+#[test]
+fn runs_selftest_std_to_file() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("std_to_file").assert().success();
+    Ok(())
+}
+
+#[test]
+fn runs_selftest_stdio_to_devnull() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("stdio_to_devnull").assert().success().stdout("").stderr("");
+    Ok(())
+}
+
+#[test]
+fn runs_selftest_reset_to_tty() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("reset_to_tty").assert().success();
+    Ok(())
+}
+
+#[test]
+fn runs_selftest_stdout_to_stderr() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("stdout_to_stderr").assert().success();
+    Ok(())
+}
+
+#[test]
+fn runs_selftest_stderr_to_stdout() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("stderr_to_stdout").assert().success();
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn runs_selftest_stdin_from_bytes() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("stdin_from_bytes").assert().success();
+    Ok(())
+}
+
+#[test]
+fn runs_selftest_std_to_path_with_options() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("std_to_path_with_options").assert().success();
+    Ok(())
+}
+
+#[test]
+fn runs_selftest_stdin_from_path_with_options() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("stdin_from_path_with_options").assert().success();
+    Ok(())
+}
+
+#[cfg(all(target_os = "linux", feature = "linux-memfd"))]
+#[test]
+fn runs_selftest_stdout_to_memfd() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("stdout_to_memfd").assert().success();
+    Ok(())
+}
+
+#[test]
+fn runs_selftest_std_pair() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("std_pair").assert().success();
+    Ok(())
+}
+
+#[cfg(all(windows, feature = "windows-sys"))]
+#[test]
+fn runs_selftest_stdout_to_named_pipe() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("stdout_to_named_pipe").assert().success();
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn runs_selftest_intercept_stdout() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("intercept_stdout").assert().success();
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn runs_selftest_save_restore_std() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("save_restore_std").assert().success().stdout("Hello to stdout!").stderr("Hello to stderr!");
+    Ok(())
+}
+
+#[test]
+fn runs_selftest_std_to_path_exclusive() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("std_to_path_exclusive").assert().success();
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn runs_selftest_builder() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("builder").assert().success().stdout("Hello to stdout!").stderr("Hello to stderr!");
+    Ok(())
+}
+
+#[test]
+fn runs_selftest_stdin_from_reader() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("stdin_from_reader").assert().success();
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn runs_selftest_stdout_to_syslog() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("stdout_to_syslog").assert().success().stdout("Hello to stdout!");
+    Ok(())
+}
+
+#[cfg(all(target_os = "linux", feature = "systemd"))]
+#[test]
+fn runs_selftest_std_to_journald() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("std_to_journald").assert().success().stderr("Hello to stderr!\n");
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn runs_selftest_close_inherited_fds() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("close_inherited_fds").assert().success();
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn runs_selftest_stdout_to_unix_socket() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("stdout_to_unix_socket").assert().success();
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn runs_selftest_stdout_to_tcp_stream() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("stdout_to_tcp_stream").assert().success();
+    Ok(())
+}
+
+#[test]
+fn runs_selftest_stdin_redirected_twice() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("stdin_redirected_twice").assert().success();
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn runs_selftest_std_to_path_guarded() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("std_to_path_guarded").assert().success().stdout("Hello to stdout!").stderr("Hello to stderr!");
+    Ok(())
+}
+
+#[test]
+fn runs_selftest_redirect_active() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("redirect_active").assert().success();
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn runs_selftest_capture_fd_output() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("capture_fd_output").assert().success().stdout("Hello to stdout!");
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn runs_selftest_std_to_path_with_umask() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("std_to_path_with_umask").assert().success();
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn runs_selftest_redirect_and_exec() -> Result<(), Box<dyn std::error::Error>> {
+    let tempdir = tempfile::tempdir()?;
+    let log_path = tempdir.path().join("echo.log");
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("redirect_and_exec").arg(&log_path).assert().success().stdout("");
+    assert_eq!(std::fs::read_to_string(&log_path)?, "hello\n");
+    Ok(())
+}
+
+#[test]
+fn runs_selftest_std_from_env() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("std_from_env").assert().success().stdout("").stderr("");
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn runs_selftest_redirect_while_signalled() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("redirect_while_signalled").assert().success();
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn runs_selftest_swap_std_pair() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("swap_std_pair").assert().success();
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn runs_selftest_stderr_tee_to_path() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("stderr_tee_to_path").assert().success().stderr("Hello to stderr!\n");
+    Ok(())
+}
+
+#[test]
+fn runs_selftest_try_std_to_path_with_options() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("try_std_to_path_with_options").assert().success();
+    Ok(())
+}
+
+#[test]
+fn runs_selftest_file_to_stdout() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("file_to_stdout").assert().success().stdout("Hello to stdout!");
+    Ok(())
+}
+
+#[test]
+fn runs_selftest_stderr_to_file() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("stderr_to_file").assert().success().stdout("Hello to stdout!");
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn runs_selftest_fd_range() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("fd_range").assert().success();
+    Ok(())
+}
+
+#[test]
+fn runs_selftest_stdout_to_os_string_from_env() -> Result<(), Box<dyn std::error::Error>> {
+    let mut cmd = Command::cargo_bin("examples/selftest")?;
+    cmd.arg("stdout_to_os_string_from_env").assert().success();
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn redirects_child_process_streams_to_paths() -> Result<(), Box<dyn std::error::Error>> {
+    use io_redirect::CommandRedirectExt;
+    let tempdir = tempfile::tempdir()?;
+    let input_path = tempdir.path().join("input.txt");
+    let output_path = tempdir.path().join("output.txt");
+    std::fs::write(&input_path, "abc")?;
+    let status = Command::new("sh")
+        .args(["-c", "cat; echo def >&2"])
+        .redirect_stdin_from_path(&input_path)?
+        .redirect_stdout_stderr_to_same_path(&output_path)?
+        .status()?;
+    assert!(status.success());
+    assert_eq!(std::fs::read_to_string(&output_path)?, "abcdef\n");
+    Ok(())
 }
\ No newline at end of file
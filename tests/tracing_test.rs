@@ -0,0 +1,34 @@
+#![cfg(all(unix, feature = "std", feature = "libc_on_unix", feature = "tracing"))]
+
+use std::fs::File;
+use std::sync::{Arc, Mutex};
+use io_redirect::Redirectable;
+use tracing::span::{Attributes, Id};
+use tracing::Subscriber;
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::Layer;
+
+/// Records the names of the spans created while it is installed.
+struct SpanNames(Arc<Mutex<Vec<&'static str>>>);
+
+impl<S: Subscriber> Layer<S> for SpanNames {
+    fn on_new_span(&self, attrs: &Attributes<'_>, _id: &Id, _ctx: Context<'_, S>) {
+        self.0.lock().unwrap().push(attrs.metadata().name());
+    }
+}
+
+#[test]
+fn traces_redirect_spans() {
+    // Arrange
+    let tempdir = tempfile::tempdir().unwrap();
+    let mut src = File::create(tempdir.path().join("src.txt")).unwrap();
+    let dst_path = tempdir.path().join("dst.txt");
+    let names = Arc::new(Mutex::new(Vec::new()));
+    let subscriber = tracing_subscriber::registry().with(SpanNames(names.clone()));
+
+    // Act
+    tracing::subscriber::with_default(subscriber, || src.redirect(dst_path.as_path())).unwrap();
+
+    // Assert
+    assert_eq!(*names.lock().unwrap(), vec!["open_destination", "redirect_fd_to_fd"]);
+}